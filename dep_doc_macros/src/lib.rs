@@ -0,0 +1,2211 @@
+//! Procedural macros backing the `proc-macro` feature of [`dep_doc`].
+//!
+//! This crate is an implementation detail of `dep_doc`: its API is not
+//! considered public and may change in any release.
+//!
+//! [`dep_doc`]: https://docs.rs/dep_doc
+
+mod manifest;
+
+use dep_doc_toml::Value;
+use manifest::{Entry, Manifest};
+use proc_macro::{Delimiter, Group, Literal, Punct, Spacing, TokenStream, TokenTree};
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+/// Renders a `key = value` pair as it would appear in a `Cargo.toml`.
+///
+/// The key may be a bare key (possibly hyphenated or dotted) or a string
+/// literal. The value may be a string, an integer, a float, a boolean, an
+/// array or an inline table.
+///
+/// With a leading `@string`, the key is a string literal and the value an
+/// expression expanding to a string, such as `env!("CARGO_PKG_REPOSITORY")`.
+/// String literals and `env!` invocations are escaped, other expressions are
+/// expected to expand to a string which doesn't need to be.
+#[proc_macro]
+pub fn render_key(input: TokenStream) -> TokenStream {
+    let tokens = flatten(input);
+
+    match tokens.as_slice() {
+        [at, TokenTree::Ident(string), TokenTree::Literal(key), eq, value @ ..]
+            if is_punct(at, '@') && string.to_string() == "string" && is_punct(eq, '=') =>
+        {
+            let key = match parse_string(&key.to_string()) {
+                Some(key) => key,
+                None => return compile_error(&format!("expected a string key, found `{}`", key)),
+            };
+
+            match resolve_string(value) {
+                Ok(value) => {
+                    let pair = format!("{} = {}", key, toml_string(&value));
+                    TokenTree::Literal(Literal::string(&pair)).into()
+                }
+                Err(_) => {
+                    let mut args = vec![
+                        TokenTree::Literal(Literal::string(&format!("{} = \"", key))),
+                        TokenTree::Punct(Punct::new(',', Spacing::Alone)),
+                    ];
+                    args.extend(value.iter().cloned());
+                    args.push(TokenTree::Punct(Punct::new(',', Spacing::Alone)));
+                    args.push(TokenTree::Literal(Literal::string("\"")));
+
+                    let mut concat = "::core::concat!".parse::<TokenStream>().unwrap();
+                    concat.extend(Some(TokenTree::Group(Group::new(
+                        Delimiter::Parenthesis,
+                        args.into_iter().collect(),
+                    ))));
+                    concat
+                }
+            }
+        }
+
+        tokens => match render_pair(tokens) {
+            Ok(pair) => TokenTree::Literal(Literal::string(&pair)).into(),
+            Err(message) => compile_error(&message),
+        },
+    }
+}
+
+/// Checks that every feature listed in the `features` and
+/// `recommended_features` options is declared in the manifest of the crate
+/// being compiled.
+///
+/// The input is the bracketed list of snippet options. Expands to an empty
+/// string.
+#[proc_macro]
+pub fn verify_features(input: TokenStream) -> TokenStream {
+    let tokens = flatten(input);
+
+    match check_features(&tokens) {
+        Ok(()) => TokenTree::Literal(Literal::string("")).into(),
+        Err(message) => compile_error(&message),
+    }
+}
+
+fn check_features(tokens: &[TokenTree]) -> Result<(), String> {
+    let mut requested = Vec::new();
+
+    for option in options(tokens)? {
+        if let [TokenTree::Ident(key), eq, TokenTree::Group(list)] = option.as_slice() {
+            let key = key.to_string();
+            if (key == "features" || key == "recommended_features") && is_punct(eq, '=') {
+                for item in flatten(list.stream()) {
+                    if let TokenTree::Literal(literal) = item {
+                        requested.extend(parse_string(&literal.to_string()));
+                    }
+                }
+            }
+        }
+    }
+
+    let declared = Manifest::read()?.features();
+
+    match requested
+        .iter()
+        .find(|feature| !feature.contains('/') && !declared.contains(feature))
+    {
+        Some(feature) => Err(format!(
+            "feature `{}` is not declared in the `[features]` table of `Cargo.toml`",
+            feature
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Checks that the `git` option points to the `repository` declared in the
+/// manifest of the crate being compiled.
+///
+/// The input is the bracketed list of snippet options. Expands to an empty
+/// string.
+#[proc_macro]
+pub fn verify_git(input: TokenStream) -> TokenStream {
+    let tokens = flatten(input);
+
+    match check_git(&tokens) {
+        Ok(()) => TokenTree::Literal(Literal::string("")).into(),
+        Err(message) => compile_error(&message),
+    }
+}
+
+fn check_git(tokens: &[TokenTree]) -> Result<(), String> {
+    let repository = std::env::var("CARGO_PKG_REPOSITORY").unwrap_or_default();
+
+    for option in options(tokens)? {
+        if let [TokenTree::Ident(key), eq, TokenTree::Literal(url)] = option.as_slice() {
+            if key.to_string() != "git" || !is_punct(eq, '=') {
+                continue;
+            }
+
+            let url = parse_string(&url.to_string())
+                .ok_or_else(|| "expected a string literal as `git` URL".to_string())?;
+
+            if repository.is_empty() {
+                return Err(
+                    "`verify_git` requires the `repository` field of `Cargo.toml` to be set"
+                        .to_string(),
+                );
+            }
+
+            if normalize_url(&url) != normalize_url(&repository) {
+                return Err(format!(
+                    "git URL `{}` does not match the repository `{}` declared in `Cargo.toml`",
+                    url, repository
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Reduces the different ways of spelling a repository URL (scheme, `www.`,
+// SSH shorthand, `.git` suffix, trailing slash) to `host/path`.
+fn normalize_url(url: &str) -> String {
+    let url = url.trim();
+    let url = url
+        .find("://")
+        .map_or(url, |idx| &url[idx + 3..])
+        .trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    let url = url.rsplit('@').next().unwrap_or(url);
+    let url = url.strip_prefix("www.").unwrap_or(url);
+
+    let (host, path) = match url.find(|ch| ch == '/' || ch == ':') {
+        Some(idx) => (&url[..idx], &url[idx + 1..]),
+        None => (url, ""),
+    };
+
+    format!("{}/{}", host.to_ascii_lowercase(), path)
+}
+
+/// Renders a Markdown table of the features declared in the manifest of the
+/// crate being compiled, with their default status and their `##`
+/// documentation comments.
+#[proc_macro]
+pub fn feature_doc(_input: TokenStream) -> TokenStream {
+    match Manifest::read() {
+        Ok(manifest) => TokenTree::Literal(Literal::string(&feature_table(&manifest))).into(),
+        Err(message) => compile_error(&message),
+    }
+}
+
+fn feature_table(manifest: &Manifest) -> String {
+    let features = manifest.table("features").map_or(&[][..], |t| &t.entries);
+    let enabled = default_features(features);
+    let mut table = "| Feature | Default | Description |\n|---|---|---|".to_string();
+
+    for feature in features.iter().filter(|feature| feature.key != "default") {
+        table.push_str(&format!(
+            "\n| `{}` | {} | {} |",
+            feature.key,
+            if enabled.contains(&feature.key) {
+                "yes"
+            } else {
+                "no"
+            },
+            feature.docs.join(" ").replace('|', "\\|"),
+        ));
+    }
+
+    table
+}
+
+/// Renders a Markdown table of the requested features, with the `##`
+/// documentation comments of the manifest of the crate being compiled.
+///
+/// The input is a bracketed list of feature names. Features which are not
+/// declared in the manifest are rejected.
+#[proc_macro]
+pub fn feature_descriptions(input: TokenStream) -> TokenStream {
+    let tokens = flatten(input);
+    let table = options_strings(&tokens).and_then(|features| {
+        Manifest::read().and_then(|manifest| feature_descriptions_table(&manifest, &features))
+    });
+
+    match table {
+        Ok(table) => TokenTree::Literal(Literal::string(&table)).into(),
+        Err(message) => compile_error(&message),
+    }
+}
+
+// Returns the strings of a bracketed list of string literals.
+fn options_strings(tokens: &[TokenTree]) -> Result<Vec<String>, String> {
+    let tokens = match tokens {
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::Bracket => {
+            flatten(group.stream())
+        }
+        _ => return Err("expected a list of strings".to_string()),
+    };
+
+    split_commas(&tokens)
+        .map(|item| match item {
+            [TokenTree::Literal(literal)] => parse_string(&literal.to_string())
+                .ok_or_else(|| format!("expected a string, found `{}`", literal)),
+            _ => Err(format!("expected a string, found `{}`", to_source(item))),
+        })
+        .collect()
+}
+
+fn feature_descriptions_table(manifest: &Manifest, features: &[String]) -> Result<String, String> {
+    let declared = manifest.table("features").map_or(&[][..], |t| &t.entries);
+    let known = manifest.features();
+    let mut table = "| Feature | Description |\n|---|---|".to_string();
+
+    for feature in features {
+        if !known.contains(feature) {
+            return Err(format!(
+                "feature `{}` is not declared in `Cargo.toml`, expected one of {}",
+                feature,
+                known
+                    .iter()
+                    .map(|name| format!("`{}`", name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+
+        let docs = declared
+            .iter()
+            .find(|entry| &entry.key == feature)
+            .map_or(String::new(), |entry| entry.docs.join(" "));
+        table.push_str(&format!(
+            "\n| `{}` | {} |",
+            feature,
+            docs.replace('|', "\\|")
+        ));
+    }
+
+    Ok(table)
+}
+
+// Returns the features enabled by `default`, directly or not.
+fn default_features(features: &[Entry]) -> Vec<String> {
+    let mut enabled = vec!["default".to_string()];
+    let mut idx = 0;
+
+    while idx < enabled.len() {
+        if let Some(feature) = features.iter().find(|feature| feature.key == enabled[idx]) {
+            for name in manifest::string_array(&feature.value) {
+                if !name.contains('/') && !name.starts_with("dep:") && !enabled.contains(&name) {
+                    enabled.push(name);
+                }
+            }
+        }
+
+        idx += 1;
+    }
+
+    enabled
+}
+
+/// Renders a Markdown table of the optional dependencies of the crate being
+/// compiled, with the features enabling them and their version requirement.
+#[proc_macro]
+pub fn integrations_doc(_input: TokenStream) -> TokenStream {
+    match Manifest::read() {
+        Ok(manifest) => TokenTree::Literal(Literal::string(&integrations_table(&manifest))).into(),
+        Err(message) => compile_error(&message),
+    }
+}
+
+fn integrations_table(manifest: &Manifest) -> String {
+    let features = manifest.table("features").map_or(&[][..], |t| &t.entries);
+    let known = manifest.features();
+    let mut table = "| Feature | Crate | Version |\n|---|---|---|".to_string();
+
+    for dependency in manifest.optional_dependency_specs() {
+        let name = &dependency.name;
+        let mut enabling = features
+            .iter()
+            .filter(|feature| {
+                manifest::string_array(&feature.value).iter().any(|item| {
+                    let item = item.strip_prefix("dep:").unwrap_or(item);
+                    let item = item.split('/').next().unwrap().trim_end_matches('?');
+                    item == name
+                })
+            })
+            .map(|feature| feature.key.clone())
+            .filter(|feature| feature != "default")
+            .collect::<Vec<_>>();
+
+        // The implicit feature of the dependency, unless it is hidden.
+        if known.contains(name) && !enabling.contains(name) {
+            enabling.insert(0, name.clone());
+        }
+
+        table.push_str(&format!(
+            "\n| {} | [`{1}`](https://crates.io/crates/{1}) | {2} |",
+            enabling
+                .iter()
+                .map(|feature| format!("`{}`", feature))
+                .collect::<Vec<_>>()
+                .join(", "),
+            dependency.package,
+            dependency.version.as_deref().unwrap_or("*"),
+        ));
+    }
+
+    table
+}
+
+/// Renders the command running an example of the crate being compiled, with
+/// the features it requires, followed by its `[[example]]` table if it has
+/// some.
+///
+/// The input is a bracketed list containing the name of the example. Examples
+/// which are neither declared in the manifest nor found in the `examples`
+/// directory are rejected.
+#[proc_macro]
+pub fn example_doc(input: TokenStream) -> TokenStream {
+    let tokens = flatten(input);
+    let snippet = options_strings(&tokens).and_then(|names| match names.as_slice() {
+        [name] => {
+            let dir = manifest::manifest_dir()?;
+            Manifest::read_from(&dir).and_then(|manifest| example_snippet(&manifest, &dir, name))
+        }
+        _ => Err("expected the name of an example".to_string()),
+    });
+
+    match snippet {
+        Ok(snippet) => TokenTree::Literal(Literal::string(&snippet)).into(),
+        Err(message) => compile_error(&message),
+    }
+}
+
+fn example_snippet(manifest: &Manifest, dir: &Path, name: &str) -> Result<String, String> {
+    let declared = manifest
+        .tables
+        .iter()
+        .filter(|table| table.header == "example")
+        .find(|table| {
+            table
+                .entries
+                .iter()
+                .any(|entry| entry.key == "name" && manifest::string_array(&entry.value) == [name])
+        });
+
+    let is_discovered = dir.join("examples").join(format!("{}.rs", name)).is_file()
+        || dir.join("examples").join(name).join("main.rs").is_file();
+    if declared.is_none() && !is_discovered {
+        return Err(format!("example `{}` does not exist", name));
+    }
+
+    let features = declared
+        .and_then(|table| {
+            table
+                .entries
+                .iter()
+                .find(|entry| entry.key == "required-features")
+        })
+        .map(|entry| manifest::string_array(&entry.value))
+        .unwrap_or_default();
+
+    if features.is_empty() {
+        return Ok(format!("```console\n$ cargo run --example {}\n```", name));
+    }
+
+    Ok(format!(
+        "```console\n$ cargo run --example {0} --features {1}\n```\n\n\
+        ```TOML\n[[example]]\nname = \"{0}\"\nrequired-features = [{2}]\n```",
+        name,
+        features.join(","),
+        features
+            .iter()
+            .map(|feature| format!("\"{}\"", feature))
+            .collect::<Vec<_>>()
+            .join(", "),
+    ))
+}
+
+/// Renders a Markdown table of the publishable packages of the workspace
+/// containing the crate being compiled, with their current version.
+#[proc_macro]
+pub fn workspace_members_doc(_input: TokenStream) -> TokenStream {
+    let members = manifest::manifest_dir().and_then(|dir| workspace_members(&dir));
+
+    match members {
+        Ok(members) => TokenTree::Literal(Literal::string(&members_table(&members))).into(),
+        Err(message) => compile_error(&message),
+    }
+}
+
+// Returns the root of the workspace `dir` belongs to, and its manifest. A
+// package which is not part of a workspace is its own root.
+fn workspace_root(dir: &Path) -> Result<(&Path, Manifest), String> {
+    let root = dir
+        .ancestors()
+        .filter(|dir| dir.join("Cargo.toml").is_file())
+        .map(|dir| Manifest::read_from(dir).map(|manifest| (dir, manifest)))
+        .find(|manifest| match manifest {
+            Ok((_, manifest)) => manifest.table("workspace").is_some(),
+            Err(_) => true,
+        })
+        .transpose()?;
+
+    match root {
+        Some(root) => Ok(root),
+        None => Ok((dir, Manifest::read_from(dir)?)),
+    }
+}
+
+// Returns the name and version of the publishable packages of the workspace
+// `dir` belongs to, the root package first.
+fn workspace_members(dir: &Path) -> Result<Vec<(String, String)>, String> {
+    let (root, workspace) = workspace_root(dir)?;
+
+    let excluded = workspace
+        .value("workspace", "exclude")
+        .map(manifest::string_array)
+        .unwrap_or_default();
+
+    let mut dirs = vec![root.to_path_buf()];
+    for member in workspace
+        .value("workspace", "members")
+        .map(manifest::string_array)
+        .unwrap_or_default()
+    {
+        for dir in expand_member(root, &member)? {
+            if !dirs.contains(&dir) && !excluded.iter().any(|excluded| root.join(excluded) == dir) {
+                dirs.push(dir);
+            }
+        }
+    }
+
+    let mut members = Vec::new();
+    for dir in dirs {
+        let manifest = Manifest::read_from(&dir)?;
+        let name = match manifest.value("package", "name") {
+            Some(name) => manifest::string_array(name).remove(0),
+            None => continue,
+        };
+
+        match manifest.value("package", "publish") {
+            Some(Value::Bool(false)) => continue,
+            Some(Value::Array(registries)) if registries.is_empty() => continue,
+            _ => {}
+        }
+
+        // `version.workspace = true` and `version = { workspace = true }`.
+        let version = manifest.value("package", "version");
+        let is_inherited = manifest.value("package", "version.workspace")
+            == Some(&Value::Bool(true))
+            || matches!(version, Some(Value::Table(_)));
+        let version = if is_inherited {
+            workspace.value("workspace.package", "version")
+        } else {
+            version
+        };
+        let version = version
+            .map(|version| manifest::string_array(version).remove(0))
+            .unwrap_or_else(|| "0.0.0".to_string());
+
+        members.push((name, version));
+    }
+
+    Ok(members)
+}
+
+// Expands a `members` entry, whose last component may contain a `*`.
+fn expand_member(root: &Path, member: &str) -> Result<Vec<PathBuf>, String> {
+    let path = root.join(member);
+    let pattern = match path.file_name().and_then(|name| name.to_str()) {
+        Some(pattern) if pattern.contains('*') => pattern.to_string(),
+        _ => return Ok(vec![path]),
+    };
+
+    let parent = path.parent().unwrap();
+    let star = pattern.find('*').unwrap();
+    let (prefix, suffix) = (&pattern[..star], &pattern[star + 1..]);
+
+    let entries = fs::read_dir(parent)
+        .map_err(|e| format!("failed to read `{}`: {}", parent.display(), e))?;
+    let mut dirs = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|dir| dir.join("Cargo.toml").is_file())
+        .filter(|dir| {
+            dir.file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |name| {
+                    name.len() >= prefix.len() + suffix.len()
+                        && name.starts_with(prefix)
+                        && name.ends_with(suffix)
+                })
+        })
+        .collect::<Vec<_>>();
+
+    dirs.sort();
+    Ok(dirs)
+}
+
+fn members_table(members: &[(String, String)]) -> String {
+    let mut table = "| Crate | Version |\n|---|---|".to_string();
+
+    for (name, version) in members {
+        table.push_str(&format!(
+            "\n| [`{0}`](https://crates.io/crates/{0}) | {1} |",
+            name, version
+        ));
+    }
+
+    table
+}
+
+/// Expands to the path of the crate being compiled, relative to the root of
+/// its workspace.
+///
+/// The input is a bracketed list which may contain a base directory, relative
+/// to the workspace root. The path is then relative to this directory
+/// instead. Components are separated by `/` on every platform.
+#[proc_macro]
+pub fn relative_path(input: TokenStream) -> TokenStream {
+    let tokens = flatten(input);
+    let path = options_strings(&tokens).and_then(|base| {
+        let dir = manifest::manifest_dir()?;
+        let (root, _) = workspace_root(&dir)?;
+
+        match base.as_slice() {
+            [] => Ok(path_between(root, &dir)),
+            [base] => Ok(path_between(&root.join(base), &dir)),
+            _ => Err("expected at most one base directory".to_string()),
+        }
+    });
+
+    match path {
+        Ok(path) => TokenTree::Literal(Literal::string(&path)).into(),
+        Err(message) => compile_error(&message),
+    }
+}
+
+// Returns the path leading from the `from` directory to `to`.
+fn path_between(from: &Path, to: &Path) -> String {
+    let (from, to) = (normalize(from), normalize(to));
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let path = from[common..]
+        .iter()
+        .map(|_| "..".to_string())
+        .chain(
+            to[common..]
+                .iter()
+                .map(|component| component.as_os_str().to_string_lossy().into_owned()),
+        )
+        .collect::<Vec<_>>();
+
+    if path.is_empty() {
+        ".".to_string()
+    } else {
+        path.join("/")
+    }
+}
+
+// Removes the `.` components of `path`, and the `..` components following a
+// directory name.
+fn normalize(path: &Path) -> Vec<Component<'_>> {
+    let mut components = Vec::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if matches!(components.last(), Some(Component::Normal(_))) => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+
+    components
+}
+
+/// Expands to the `docsrs` branch when the crate is built by docs.rs, and to
+/// the `local` branch otherwise.
+///
+/// The input is a bracketed `docsrs => ..., local => ...` list. docs.rs is
+/// detected with the `DOCS_RS` environment variable it sets.
+#[proc_macro]
+pub fn docsrs_select(input: TokenStream) -> TokenStream {
+    let tokens = flatten(input);
+    let branch = if std::env::var_os("DOCS_RS").is_some() {
+        "docsrs"
+    } else {
+        "local"
+    };
+
+    match select_branch(&tokens, branch) {
+        Ok(tokens) => tokens,
+        Err(message) => compile_error(&message),
+    }
+}
+
+fn select_branch(tokens: &[TokenTree], branch: &str) -> Result<TokenStream, String> {
+    let tokens = match tokens {
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::Bracket => {
+            group.stream().into_iter().collect::<Vec<_>>()
+        }
+        _ => return Err("expected `docsrs => ..., local => ...`".to_string()),
+    };
+
+    for arm in split_commas(&tokens) {
+        match arm {
+            [TokenTree::Ident(name), eq, gt, body @ ..]
+                if is_punct(eq, '=') && is_punct(gt, '>') && !body.is_empty() =>
+            {
+                let name = name.to_string();
+                if name != "docsrs" && name != "local" {
+                    return Err(format!(
+                        "unknown branch `{}`, expected `docsrs` or `local`",
+                        name
+                    ));
+                }
+
+                if name == branch {
+                    return Ok(body.iter().cloned().collect());
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "expected `name => ...`, found `{}`",
+                    to_source(arm)
+                ))
+            }
+        }
+    }
+
+    Err(format!("missing the `{}` branch", branch))
+}
+
+/// Inserts the [`dep_doc`] snippet in the documentation of an item.
+///
+/// Unlike `#![doc = dep_doc::dep_doc!()]`, which can only be placed before or
+/// after the other inner doc attributes, this attribute splices the snippet
+/// within the existing documentation of a module, a struct, a function or any
+/// other documented item. The `position` option tells where the snippet goes:
+/// `"start"`, `"after_first_paragraph"` or `"end"` (the default). The other
+/// options are passed to [`dep_doc`]:
+///
+/// ```rust,ignore
+/// /// A fancy client.
+/// ///
+/// /// It connects to a fancy server.
+/// #[dep_doc::inject(position = "after_first_paragraph", features = ["client"])]
+/// pub struct Client;
+/// ```
+///
+/// The generated attribute refers to the snippet as `::dep_doc::dep_doc!`, so
+/// the crate must depend on `dep_doc` under its original name.
+///
+/// [`dep_doc`]: https://docs.rs/dep_doc/latest/dep_doc/macro.dep_doc.html
+#[proc_macro_attribute]
+pub fn inject(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = args.into_iter().collect::<Vec<_>>();
+    let item = item.into_iter().collect::<Vec<_>>();
+
+    match inject_snippet(&args, &item) {
+        Ok(tokens) => tokens,
+        Err(message) => {
+            let mut tokens = compile_error(&message);
+            tokens.extend(vec![TokenTree::Punct(Punct::new(';', Spacing::Alone))]);
+            tokens.extend(item);
+            tokens
+        }
+    }
+}
+
+fn inject_snippet(args: &[TokenTree], item: &[TokenTree]) -> Result<TokenStream, String> {
+    let mut position = Position::End;
+    let mut options = Vec::new();
+
+    for option in split_commas(args) {
+        match option {
+            [TokenTree::Ident(key), eq, TokenTree::Literal(value)]
+                if key.to_string() == "position" && is_punct(eq, '=') =>
+            {
+                position = parse_string(&value.to_string())
+                    .and_then(|value| Position::parse(&value))
+                    .ok_or_else(|| {
+                        format!(
+                            "unknown position `{}`, expected one of `\"start\"`, \
+                             `\"after_first_paragraph\"` or `\"end\"`",
+                            value
+                        )
+                    })?;
+            }
+            option => {
+                if !options.is_empty() {
+                    options.push(TokenTree::Punct(Punct::new(',', Spacing::Alone)));
+                }
+                options.extend(option.iter().cloned());
+            }
+        }
+    }
+
+    // The outer attributes, followed by the item itself.
+    let mut attrs = Vec::new();
+    let mut rest = item;
+    while let [pound, TokenTree::Group(group), tail @ ..] = rest {
+        if !is_punct(pound, '#') || group.delimiter() != Delimiter::Bracket {
+            break;
+        }
+
+        attrs.push([pound.clone(), TokenTree::Group(group.clone())]);
+        rest = tail;
+    }
+
+    let docs = attrs
+        .iter()
+        .map(|[_, attr]| doc_content(attr))
+        .collect::<Vec<_>>();
+    let (idx, blank_before, blank_after) = insertion_point(&docs, position);
+
+    let mut snippet = "::dep_doc::dep_doc!".parse::<TokenStream>().unwrap();
+    snippet.extend(vec![TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        options.into_iter().collect(),
+    ))]);
+
+    let mut inserted = Vec::new();
+    if blank_before {
+        inserted.push(doc_attr(TokenTree::Literal(Literal::string("")).into()));
+    }
+    inserted.push(doc_attr(snippet));
+    if blank_after {
+        inserted.push(doc_attr(TokenTree::Literal(Literal::string("")).into()));
+    }
+
+    let mut tokens = TokenStream::new();
+    tokens.extend(attrs[..idx].iter().flat_map(|attr| attr.iter().cloned()));
+    tokens.extend(inserted.into_iter().flatten());
+    tokens.extend(attrs[idx..].iter().flat_map(|attr| attr.iter().cloned()));
+    tokens.extend(rest.iter().cloned());
+
+    Ok(tokens)
+}
+
+#[derive(Clone, Copy)]
+enum Position {
+    Start,
+    AfterFirstParagraph,
+    End,
+}
+
+impl Position {
+    fn parse(position: &str) -> Option<Position> {
+        match position {
+            "start" => Some(Position::Start),
+            "after_first_paragraph" => Some(Position::AfterFirstParagraph),
+            "end" => Some(Position::End),
+            _ => None,
+        }
+    }
+}
+
+// Returns the text of a `#[doc = "..."]` attribute, or `None` for the other
+// attributes.
+fn doc_content(attr: &TokenTree) -> Option<String> {
+    let group = match attr {
+        TokenTree::Group(group) => group,
+        _ => return None,
+    };
+
+    match flatten(group.stream()).as_slice() {
+        [TokenTree::Ident(doc), eq, TokenTree::Literal(content)]
+            if doc.to_string() == "doc" && is_punct(eq, '=') =>
+        {
+            parse_string(&content.to_string())
+        }
+        _ => None,
+    }
+}
+
+// Returns the index of the attribute before which the snippet is inserted,
+// and whether it must be preceded and followed by an empty line.
+fn insertion_point(docs: &[Option<String>], position: Position) -> (usize, bool, bool) {
+    let mut lines = docs
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, doc)| doc.as_ref().map(|doc| (idx, doc.trim())));
+
+    match position {
+        Position::Start => match lines.next() {
+            Some((idx, _)) => (idx, false, true),
+            None => (docs.len(), false, false),
+        },
+        Position::AfterFirstParagraph => {
+            let mut lines = lines.skip_while(|(_, line)| line.is_empty()).peekable();
+            if lines.peek().is_none() {
+                return (docs.len(), false, false);
+            }
+
+            match lines.find(|(_, line)| line.is_empty()) {
+                Some((idx, _)) => (idx, true, false),
+                None => (docs.len(), true, false),
+            }
+        }
+        Position::End => (docs.len(), lines.next().is_some(), false),
+    }
+}
+
+fn doc_attr(value: TokenStream) -> Vec<TokenTree> {
+    let mut content = "doc =".parse::<TokenStream>().unwrap();
+    content.extend(value);
+
+    vec![
+        TokenTree::Punct(Punct::new('#', Spacing::Alone)),
+        TokenTree::Group(Group::new(Delimiter::Bracket, content)),
+    ]
+}
+
+/// Applies the `pin` or `deny` pre-release policy to a version requirement.
+///
+/// The input is a bracketed `policy, [pre], version` list, where `pre` is the
+/// pre-release suffix of the version, either as a string literal or as an
+/// `env!` invocation, and `version` is the rendered requirement.
+#[proc_macro]
+pub fn prerelease(input: TokenStream) -> TokenStream {
+    let tokens = flatten(input);
+
+    match apply_prerelease(&tokens) {
+        Ok(tokens) => tokens,
+        Err(message) => compile_error(&message),
+    }
+}
+
+fn apply_prerelease(tokens: &[TokenTree]) -> Result<TokenStream, String> {
+    let tokens = match tokens {
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::Bracket => {
+            group.stream().into_iter().collect::<Vec<_>>()
+        }
+        _ => return Err("expected `policy, [pre], version`".to_string()),
+    };
+
+    let (policy, pre, version) = match tokens.as_slice() {
+        [TokenTree::Ident(policy), comma, TokenTree::Group(pre), comma_, version @ ..]
+            if is_punct(comma, ',') && is_punct(comma_, ',') =>
+        {
+            (policy.to_string(), flatten(pre.stream()), version)
+        }
+        _ => return Err("expected `policy, [pre], version`".to_string()),
+    };
+
+    let version = version.iter().cloned().collect::<TokenStream>();
+    let pre = resolve_string(&pre)?;
+    let is_prerelease = !pre.is_empty();
+
+    match policy.as_str() {
+        "pin" if is_prerelease => {
+            let mut pinned = TokenStream::new();
+            pinned.extend(vec![
+                TokenTree::Literal(Literal::string("=")),
+                TokenTree::Punct(Punct::new(',', Spacing::Alone)),
+            ]);
+            pinned.extend(version);
+
+            let mut tokens = "::core::concat!".parse::<TokenStream>().unwrap();
+            tokens.extend(vec![TokenTree::Group(Group::new(
+                Delimiter::Parenthesis,
+                pinned,
+            ))]);
+            Ok(tokens)
+        }
+        "deny" if is_prerelease => Err(format!(
+            "the documented version is a pre-release (`-{}`), which is denied by `prerelease = deny`",
+            pre
+        )),
+        "pin" | "deny" => Ok(version),
+        _ => Err(format!(
+            "unknown pre-release policy `{}`, expected one of `keep`, `strip`, `pin` or `deny`",
+            policy
+        )),
+    }
+}
+
+/// Adds the features the crate is compiled with to the `features` option of a
+/// snippet, and renders it again.
+///
+/// The input is a bracketed `[$crate] ctx [options] [exclude = [...]]` list,
+/// where `ctx` is the first argument of `render_section`. The enabled features
+/// are read from the `DEP_DOC_FEATURES` environment variable, which is set by
+/// `dep_doc::export_features` in the build script, or from the
+/// `CARGO_FEATURE_*` environment variables. The default features and the
+/// `exclude`d ones are skipped.
+#[proc_macro]
+pub fn enabled_features(input: TokenStream) -> TokenStream {
+    let tokens = flatten(input);
+
+    match reflect_features(&tokens) {
+        Ok(tokens) => tokens,
+        Err(message) => compile_error(&message),
+    }
+}
+
+fn reflect_features(tokens: &[TokenTree]) -> Result<TokenStream, String> {
+    let invalid = || "expected `[$crate] ctx [options] [exclude = [...]]`".to_string();
+    let tokens = match tokens {
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::Bracket => {
+            group.stream().into_iter().collect::<Vec<_>>()
+        }
+        _ => return Err(invalid()),
+    };
+
+    let (krate, ctx, options, args) = match tokens.as_slice() {
+        [TokenTree::Group(krate), ctx, TokenTree::Group(options), TokenTree::Group(args)] => (
+            krate.stream(),
+            ctx.clone(),
+            options.stream(),
+            flatten(args.stream()),
+        ),
+        _ => return Err(invalid()),
+    };
+
+    let exclude = match args.as_slice() {
+        [] => Vec::new(),
+        [TokenTree::Ident(key), eq, list] if key.to_string() == "exclude" && is_punct(eq, '=') => {
+            options_strings(std::slice::from_ref(list))?
+        }
+        _ => {
+            return Err(format!(
+                "expected `exclude = [...]`, found `{}`",
+                to_source(&args)
+            ))
+        }
+    };
+
+    let active = match std::env::var("DEP_DOC_FEATURES") {
+        Ok(features) => features.split(',').map(str::to_string).collect(),
+        Err(_) => std::env::vars()
+            .filter_map(|(key, _)| key.strip_prefix("CARGO_FEATURE_").map(str::to_string))
+            .collect::<Vec<_>>(),
+    };
+    let manifest = Manifest::read()?;
+    let enabled = enabled_features_of(&manifest, &active, &exclude);
+
+    let mut features = Vec::new();
+    let mut rendered = TokenStream::new();
+    for option in options {
+        let tokens = match &option {
+            TokenTree::Group(group) => flatten(group.stream()),
+            _ => continue,
+        };
+
+        match tokens.as_slice() {
+            [TokenTree::Ident(key), ..] if key.to_string() == "enabled_features" => {}
+            [TokenTree::Ident(key), eq, list]
+                if key.to_string() == "features" && is_punct(eq, '=') =>
+            {
+                features.extend(options_strings(std::slice::from_ref(list))?)
+            }
+            _ => rendered.extend(Some(option)),
+        }
+    }
+
+    for feature in enabled {
+        if !features.contains(&feature) {
+            features.push(feature);
+        }
+    }
+
+    if !features.is_empty() {
+        let list = features
+            .iter()
+            .map(|feature| TokenTree::Literal(Literal::string(feature)).into())
+            .collect::<Vec<TokenStream>>();
+        let mut items = TokenStream::new();
+        for (idx, feature) in list.into_iter().enumerate() {
+            if idx > 0 {
+                items.extend(Some(TokenTree::Punct(Punct::new(',', Spacing::Alone))));
+            }
+            items.extend(feature);
+        }
+
+        let mut option = "features =".parse::<TokenStream>().unwrap();
+        option.extend(Some(TokenTree::Group(Group::new(
+            Delimiter::Bracket,
+            items,
+        ))));
+        rendered.extend(Some(TokenTree::Group(Group::new(
+            Delimiter::Bracket,
+            option,
+        ))));
+    }
+
+    let mut args = TokenStream::new();
+    args.extend(vec![
+        ctx,
+        TokenTree::Group(Group::new(Delimiter::Bracket, rendered)),
+    ]);
+
+    let mut tokens = krate;
+    tokens.extend("::render_section!".parse::<TokenStream>().unwrap());
+    tokens.extend(Some(TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        args,
+    ))));
+    Ok(tokens)
+}
+
+/// Checks a `cfg(...)` predicate written as a string.
+///
+/// The input is a bracketed `[$crate] "cfg(...)"` list. The string is parsed
+/// as Rust tokens, and the predicate is handed to `check_cfg_predicate`, which
+/// validates it as it does for the predicates written as tokens.
+#[proc_macro]
+pub fn cfg_predicate(input: TokenStream) -> TokenStream {
+    let tokens = flatten(input);
+
+    match check_cfg_string(&tokens) {
+        Ok(tokens) => tokens,
+        Err(message) => compile_error(&message),
+    }
+}
+
+fn check_cfg_string(tokens: &[TokenTree]) -> Result<TokenStream, String> {
+    let invalid = || "expected `[$crate] \"cfg(...)\"`".to_string();
+    let tokens = match tokens {
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::Bracket => {
+            flatten(group.stream())
+        }
+        _ => return Err(invalid()),
+    };
+
+    let (krate, cfg) = match tokens.as_slice() {
+        [TokenTree::Group(krate), TokenTree::Literal(cfg)] => (
+            krate.stream(),
+            parse_string(&cfg.to_string()).ok_or_else(invalid)?,
+        ),
+        _ => return Err(invalid()),
+    };
+
+    let malformed = || format!("invalid `cfg` predicate: `{}`", cfg);
+    let predicate = match cfg
+        .parse::<TokenStream>()
+        .map_err(|_| malformed())?
+        .into_iter()
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [TokenTree::Ident(ident), TokenTree::Group(predicate)]
+            if ident.to_string() == "cfg" && predicate.delimiter() == Delimiter::Parenthesis =>
+        {
+            predicate.stream()
+        }
+        _ => return Err(malformed()),
+    };
+
+    let mut tokens = krate;
+    tokens.extend("::check_cfg_predicate!".parse::<TokenStream>().unwrap());
+    tokens.extend(Some(TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        predicate,
+    ))));
+    Ok(tokens)
+}
+
+// Returns the non-default features of the crate which are in `active`, in the
+// order of the manifest. The names of `active` may be written as in the
+// `CARGO_FEATURE_*` environment variables.
+fn enabled_features_of(manifest: &Manifest, active: &[String], exclude: &[String]) -> Vec<String> {
+    let normalize = |feature: &str| feature.to_ascii_lowercase().replace('-', "_");
+    let active = active
+        .iter()
+        .map(|feature| normalize(feature))
+        .collect::<Vec<_>>();
+    let default = manifest
+        .value("features", "default")
+        .map(manifest::string_array)
+        .unwrap_or_default();
+
+    manifest
+        .features()
+        .into_iter()
+        .filter(|feature| feature != "default")
+        .filter(|feature| !default.contains(feature) && !exclude.contains(feature))
+        .filter(|feature| active.contains(&normalize(feature)))
+        .collect()
+}
+
+/// Wraps a snippet into a `<div class="dep-doc"><pre>` HTML block.
+///
+/// The input is a bracketed expression expanding to the content of the
+/// snippet. As it can't be expanded here, the `&`, `<` and `>` characters of
+/// the string literals it is made of are escaped instead, including the ones
+/// passed to the macros it invokes.
+#[proc_macro]
+pub fn html(input: TokenStream) -> TokenStream {
+    let tokens = flatten(input);
+    let content = match tokens.as_slice() {
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::Bracket => {
+            escape_literals(group.stream())
+        }
+        _ => return compile_error("expected a bracketed snippet"),
+    };
+
+    let mut wrapped = TokenStream::new();
+    wrapped.extend(vec![
+        TokenTree::Literal(Literal::string("<div class=\"dep-doc\"><pre>")),
+        TokenTree::Punct(Punct::new(',', Spacing::Alone)),
+    ]);
+    wrapped.extend(content);
+    wrapped.extend(vec![
+        TokenTree::Punct(Punct::new(',', Spacing::Alone)),
+        TokenTree::Literal(Literal::string("</pre></div>")),
+    ]);
+
+    let mut tokens = "::core::concat!".parse::<TokenStream>().unwrap();
+    tokens.extend(vec![TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        wrapped,
+    ))]);
+    tokens
+}
+
+fn escape_literals(stream: TokenStream) -> TokenStream {
+    stream
+        .into_iter()
+        .map(|token| match token {
+            TokenTree::Group(group) => {
+                let mut escaped = Group::new(group.delimiter(), escape_literals(group.stream()));
+                escaped.set_span(group.span());
+                TokenTree::Group(escaped)
+            }
+            TokenTree::Literal(literal) => match parse_string(&literal.to_string()) {
+                Some(value) if value.contains(|ch| "&<>".contains(ch)) => {
+                    let mut escaped = Literal::string(&escape_html(&value));
+                    escaped.set_span(literal.span());
+                    TokenTree::Literal(escaped)
+                }
+                _ => TokenTree::Literal(literal),
+            },
+            token => token,
+        })
+        .collect()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Fails the build when the version of the crate being compiled is a
+/// pre-release or has build metadata.
+///
+/// The input is a bracketed list containing the name of an environment
+/// variable, if any. The check only happens when this variable is set to a
+/// non-empty value, and the expansion reads it with `option_env!` so that
+/// rustc rebuilds the crate when it changes.
+#[proc_macro]
+pub fn deny_prerelease(input: TokenStream) -> TokenStream {
+    let tokens = flatten(input);
+
+    match check_prerelease(&tokens) {
+        Ok(tokens) => tokens,
+        Err(message) => {
+            let mut tokens = compile_error(&message);
+            tokens.extend(vec![TokenTree::Punct(Punct::new(';', Spacing::Alone))]);
+            tokens
+        }
+    }
+}
+
+fn check_prerelease(tokens: &[TokenTree]) -> Result<TokenStream, String> {
+    let tokens = match tokens {
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::Bracket => {
+            flatten(group.stream())
+        }
+        _ => return Err("expected `[\"ENV_VAR\"]` or `[]`".to_string()),
+    };
+
+    let guard = match tokens.as_slice() {
+        [] => None,
+        [TokenTree::Literal(name)] => Some(
+            parse_string(&name.to_string())
+                .ok_or_else(|| format!("expected a string literal, found `{}`", name))?,
+        ),
+        _ => return Err("expected `[\"ENV_VAR\"]` or `[]`".to_string()),
+    };
+
+    let version = std::env::var("CARGO_PKG_VERSION")
+        .map_err(|_| "`CARGO_PKG_VERSION` is not set".to_string())?;
+    let is_enabled = guard.as_ref().map_or(true, |name| {
+        std::env::var(name).map_or(false, |value| !value.is_empty())
+    });
+
+    if is_enabled && is_prerelease(&version) {
+        return Err(match guard {
+            Some(name) => format!(
+                "the version `{}` is a pre-release, which is denied when `{}` is set",
+                version, name
+            ),
+            None => format!("the version `{}` is a pre-release", version),
+        });
+    }
+
+    Ok(match guard {
+        Some(name) => format!(
+            "const _: ::core::option::Option<&str> = ::core::option_env!({:?});",
+            name
+        )
+        .parse()
+        .unwrap(),
+        None => TokenStream::new(),
+    })
+}
+
+// Whether a version has a pre-release suffix or build metadata.
+fn is_prerelease(version: &str) -> bool {
+    version.contains('-') || version.contains('+')
+}
+
+// Returns the value of a string literal, or of an `env!("NAME")` invocation.
+fn resolve_string(tokens: &[TokenTree]) -> Result<String, String> {
+    let invalid = || {
+        format!(
+            "expected a string literal or `env!`, found `{}`",
+            to_source(tokens)
+        )
+    };
+
+    match tokens {
+        [TokenTree::Literal(literal)] => parse_string(&literal.to_string()).ok_or_else(invalid),
+        [.., TokenTree::Ident(env), bang, TokenTree::Group(args)]
+            if env.to_string() == "env" && is_punct(bang, '!') =>
+        {
+            match flatten(args.stream()).as_slice() {
+                [TokenTree::Literal(name)] => {
+                    let name = parse_string(&name.to_string()).ok_or_else(invalid)?;
+                    std::env::var(&name)
+                        .map_err(|_| format!("environment variable `{}` not defined", name))
+                }
+                _ => Err(invalid()),
+            }
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Renders a comment placed above a dependency, substituting its `{version}`
+/// and `{msrv}` placeholders.
+///
+/// The input is a bracketed `indent, "text", [version], [msrv]` list. The
+/// placeholders are replaced by the `version` and `msrv` expressions, so that
+/// the `rust-version` of the crate is only required when `{msrv}` is used.
+#[proc_macro]
+pub fn comment(input: TokenStream) -> TokenStream {
+    let tokens = flatten(input);
+
+    match render_comment(&tokens) {
+        Ok(tokens) => tokens,
+        Err(message) => compile_error(&message),
+    }
+}
+
+fn render_comment(tokens: &[TokenTree]) -> Result<TokenStream, String> {
+    let invalid = || "expected `indent, \"text\", [version], [msrv]`".to_string();
+    let tokens = match tokens {
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::Bracket => {
+            group.stream().into_iter().collect::<Vec<_>>()
+        }
+        _ => return Err(invalid()),
+    };
+
+    let (indent, text, version, msrv) = match split_commas(&tokens).collect::<Vec<_>>()[..] {
+        [indent, text, [TokenTree::Group(version)], [TokenTree::Group(msrv)]] => {
+            let text = match flatten(text.iter().cloned().collect()).as_slice() {
+                [TokenTree::Literal(text)] => parse_string(&text.to_string()),
+                _ => None,
+            };
+            (
+                indent,
+                text.ok_or_else(invalid)?,
+                version.stream(),
+                msrv.stream(),
+            )
+        }
+        _ => return Err(invalid()),
+    };
+
+    let mut pieces = Vec::new();
+    for line in text.lines() {
+        check_comment_text("comment", line)?;
+        pieces.push(indent.iter().cloned().collect());
+        pieces.push(string_literal(if line.is_empty() { "#" } else { "# " }));
+
+        for piece in placeholders(line, &["version", "msrv"])? {
+            pieces.push(match piece {
+                Piece::Text(text) => string_literal(text),
+                Piece::Placeholder("version") => version.clone(),
+                Piece::Placeholder(_) => msrv.clone(),
+            });
+        }
+
+        pieces.push(string_literal("\n"));
+    }
+
+    let mut args = TokenStream::new();
+    for piece in pieces {
+        args.extend(piece);
+        args.extend(vec![TokenTree::Punct(Punct::new(',', Spacing::Alone))]);
+    }
+
+    let mut tokens = "::core::concat!".parse::<TokenStream>().unwrap();
+    tokens.extend(vec![TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        args,
+    ))]);
+    Ok(tokens)
+}
+
+/// Renders the lines of a compatibility matrix, padding the left column to
+/// its widest entry so that the arrows are aligned.
+///
+/// The input is a bracketed `indent, [("left", "right"), ...]` list.
+#[proc_macro]
+pub fn compat_matrix(input: TokenStream) -> TokenStream {
+    let tokens = flatten(input);
+
+    match render_compat_matrix(&tokens) {
+        Ok(tokens) => tokens,
+        Err(message) => compile_error(&message),
+    }
+}
+
+fn render_compat_matrix(tokens: &[TokenTree]) -> Result<TokenStream, String> {
+    let invalid = || "expected `indent, [(\"left\", \"right\"), ...]`".to_string();
+    let tokens = match tokens {
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::Bracket => {
+            group.stream().into_iter().collect::<Vec<_>>()
+        }
+        _ => return Err(invalid()),
+    };
+
+    let (indent, pairs) = match split_commas(&tokens).collect::<Vec<_>>()[..] {
+        [indent, [TokenTree::Group(pairs)]] if pairs.delimiter() == Delimiter::Bracket => {
+            (indent, flatten(pairs.stream()))
+        }
+        _ => return Err(invalid()),
+    };
+
+    let mut rows = Vec::new();
+    for pair in split_commas(&pairs) {
+        let pair = match pair {
+            [TokenTree::Group(pair)] if pair.delimiter() == Delimiter::Parenthesis => {
+                flatten(pair.stream())
+            }
+            _ => return Err(invalid()),
+        };
+
+        match split_commas(&pair).collect::<Vec<_>>()[..] {
+            [[TokenTree::Literal(left)], [TokenTree::Literal(right)]] => rows.push((
+                parse_string(&left.to_string()).ok_or_else(invalid)?,
+                parse_string(&right.to_string()).ok_or_else(invalid)?,
+            )),
+            _ => return Err(invalid()),
+        }
+    }
+
+    let width = rows
+        .iter()
+        .map(|(left, _)| left.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut args = TokenStream::new();
+    for (left, right) in rows {
+        let line = format!(
+            "# {}{} => {}\n",
+            left,
+            " ".repeat(width - left.chars().count()),
+            right
+        );
+        args.extend(indent.iter().cloned());
+        args.extend(vec![TokenTree::Punct(Punct::new(',', Spacing::Alone))]);
+        args.extend(string_literal(&line));
+        args.extend(vec![TokenTree::Punct(Punct::new(',', Spacing::Alone))]);
+    }
+
+    let mut tokens = "::core::concat!".parse::<TokenStream>().unwrap();
+    tokens.extend(vec![TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        args,
+    ))]);
+    Ok(tokens)
+}
+
+/// Substitutes the `{name}`, `{version}` and `{repository}` placeholders of a
+/// string.
+///
+/// The input is a bracketed `"text", [name], [version], [repository]` list,
+/// and the output is a `concat!` of the text and the expressions.
+#[proc_macro]
+pub fn interpolate(input: TokenStream) -> TokenStream {
+    let tokens = flatten(input);
+
+    match render_interpolation(&tokens) {
+        Ok(tokens) => tokens,
+        Err(message) => compile_error(&message),
+    }
+}
+
+fn render_interpolation(tokens: &[TokenTree]) -> Result<TokenStream, String> {
+    let invalid = || "expected `\"text\", [name], [version], [repository]`".to_string();
+    let tokens = match tokens {
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::Bracket => {
+            group.stream().into_iter().collect::<Vec<_>>()
+        }
+        _ => return Err(invalid()),
+    };
+
+    let (text, values) = match split_commas(&tokens).collect::<Vec<_>>()[..] {
+        [text, [TokenTree::Group(name)], [TokenTree::Group(version)], [TokenTree::Group(repository)]] =>
+        {
+            let text = match flatten(text.iter().cloned().collect()).as_slice() {
+                [TokenTree::Literal(text)] => parse_string(&text.to_string()),
+                _ => None,
+            };
+            (
+                text.ok_or_else(invalid)?,
+                [name.stream(), version.stream(), repository.stream()],
+            )
+        }
+        _ => return Err(invalid()),
+    };
+
+    let [name, version, repository] = values;
+    let mut args = TokenStream::new();
+    for piece in placeholders(&text, &["name", "version", "repository"])? {
+        args.extend(match piece {
+            Piece::Text(text) => string_literal(text),
+            Piece::Placeholder("name") => name.clone(),
+            Piece::Placeholder("version") => version.clone(),
+            Piece::Placeholder(_) => repository.clone(),
+        });
+        args.extend(vec![TokenTree::Punct(Punct::new(',', Spacing::Alone))]);
+    }
+
+    let mut tokens = "::core::concat!".parse::<TokenStream>().unwrap();
+    tokens.extend(vec![TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        args,
+    ))]);
+    Ok(tokens)
+}
+
+/// Checks that the text of a snippet option can be written on a single line of
+/// the snippet, and expands to it.
+///
+/// The input is a bracketed `"option", "text"` list, where `option` is the
+/// name of the option, used in the error message.
+#[proc_macro]
+pub fn comment_text(input: TokenStream) -> TokenStream {
+    let tokens = flatten(input);
+
+    match render_comment_text(&tokens) {
+        Ok(tokens) => tokens,
+        Err(message) => compile_error(&message),
+    }
+}
+
+fn render_comment_text(tokens: &[TokenTree]) -> Result<TokenStream, String> {
+    let invalid = || "expected `\"option\", \"text\"`".to_string();
+    let tokens = match tokens {
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::Bracket => {
+            flatten(group.stream())
+        }
+        _ => return Err(invalid()),
+    };
+
+    match tokens.as_slice() {
+        [TokenTree::Literal(option), comma, TokenTree::Literal(text)] if is_punct(comma, ',') => {
+            let option = parse_string(&option.to_string()).ok_or_else(invalid)?;
+            let value = parse_string(&text.to_string()).ok_or_else(invalid)?;
+            check_comment_text(&option, &value)?;
+            Ok(TokenTree::Literal(text.clone()).into())
+        }
+        _ => Err(invalid()),
+    }
+}
+
+// Line breaks and other control characters would end the line the text is
+// written on, and the rest of the text would be read as TOML. Tabs are allowed
+// in TOML comments.
+fn check_comment_text(option: &str, text: &str) -> Result<(), String> {
+    match text.chars().find(|ch| ch.is_control() && *ch != '\t') {
+        Some(ch) => Err(format!(
+            "`{}` can't contain control characters, found `{}`",
+            option,
+            ch.escape_default()
+        )),
+        None => Ok(()),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Piece<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+// Splits a line on its placeholders, which must be part of `allowed`. Braces
+// which do not surround an identifier, such as the ones of an inline table,
+// are left as-is.
+fn placeholders<'a>(line: &'a str, allowed: &[&str]) -> Result<Vec<Piece<'a>>, String> {
+    let mut pieces = Vec::new();
+    let mut text = 0;
+    let mut pos = 0;
+
+    while let Some(start) = line[pos..].find('{').map(|start| pos + start) {
+        let name = line[start + 1..]
+            .find('}')
+            .map(|end| &line[start + 1..start + 1 + end])
+            .filter(|name| {
+                !name.is_empty()
+                    && name
+                        .chars()
+                        .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+            });
+
+        let name = match name {
+            Some(name) => name,
+            None => {
+                pos = start + 1;
+                continue;
+            }
+        };
+
+        if !allowed.contains(&name) {
+            let expected = allowed
+                .iter()
+                .map(|name| format!("`{{{}}}`", name))
+                .collect::<Vec<_>>();
+            let (last, others) = expected.split_last().unwrap();
+            return Err(format!(
+                "unknown placeholder `{{{}}}`, expected {} or {}",
+                name,
+                others.join(", "),
+                last
+            ));
+        }
+
+        pieces.push(Piece::Text(&line[text..start]));
+        pieces.push(Piece::Placeholder(name));
+        pos = start + name.len() + 2;
+        text = pos;
+    }
+
+    pieces.push(Piece::Text(&line[text..]));
+    pieces.retain(|piece| *piece != Piece::Text(""));
+    Ok(pieces)
+}
+
+fn string_literal(value: &str) -> TokenStream {
+    TokenTree::Literal(Literal::string(value)).into()
+}
+
+/// Expands to the `rust-version` of the crate being compiled.
+///
+/// The `CARGO_PKG_RUST_VERSION` environment variable is used when it is set,
+/// the manifest is read otherwise.
+#[proc_macro]
+pub fn rust_version(_input: TokenStream) -> TokenStream {
+    let version = match std::env::var("CARGO_PKG_RUST_VERSION") {
+        Ok(version) if !version.is_empty() => Ok(version),
+        _ => Manifest::read().and_then(|manifest| {
+            manifest_rust_version(&manifest)
+                .ok_or_else(|| "the `rust-version` field of `Cargo.toml` is not set".to_string())
+        }),
+    };
+
+    match version {
+        Ok(version) => TokenTree::Literal(Literal::string(&version)).into(),
+        Err(message) => compile_error(&message),
+    }
+}
+
+/// Expands to the authors of the crate being compiled, separated by commas.
+#[proc_macro]
+pub fn authors(_input: TokenStream) -> TokenStream {
+    let authors = std::env::var("CARGO_PKG_AUTHORS").unwrap_or_default();
+    let authors = authors.split(':').collect::<Vec<_>>().join(", ");
+
+    TokenTree::Literal(Literal::string(&authors)).into()
+}
+
+fn manifest_rust_version(manifest: &Manifest) -> Option<String> {
+    let package = manifest.table("package")?;
+    let entry = package
+        .entries
+        .iter()
+        .find(|entry| entry.key == "rust-version")?;
+
+    manifest::string_array(&entry.value).into_iter().next()
+}
+
+// Returns the content of each option of a bracketed list of options.
+fn options(tokens: &[TokenTree]) -> Result<Vec<Vec<TokenTree>>, String> {
+    match tokens {
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::Bracket => Ok(group
+            .stream()
+            .into_iter()
+            .filter_map(|option| match option {
+                TokenTree::Group(group) => Some(flatten(group.stream())),
+                _ => None,
+            })
+            .collect()),
+        _ => Err("expected a list of options".to_string()),
+    }
+}
+
+fn compile_error(message: &str) -> TokenStream {
+    format!("::core::compile_error!({:?})", message)
+        .parse()
+        .unwrap()
+}
+
+// Removes the invisible groups `macro_rules` wraps around forwarded
+// fragments, such as `$value:literal`.
+fn flatten(stream: TokenStream) -> Vec<TokenTree> {
+    let mut tokens = Vec::new();
+
+    for token in stream {
+        match token {
+            TokenTree::Group(group) if group.delimiter() == Delimiter::None => {
+                tokens.extend(flatten(group.stream()))
+            }
+            token => tokens.push(token),
+        }
+    }
+
+    tokens
+}
+
+fn is_punct(token: &TokenTree, ch: char) -> bool {
+    matches!(token, TokenTree::Punct(punct) if punct.as_char() == ch)
+}
+
+fn render_pair(tokens: &[TokenTree]) -> Result<String, String> {
+    let eq = tokens
+        .iter()
+        .position(|token| is_punct(token, '='))
+        .ok_or_else(|| format!("expected `key = value`, found `{}`", to_source(tokens)))?;
+
+    let key = render_key_path(&tokens[..eq])?;
+    let value = render_value(&tokens[eq + 1..])?;
+
+    Ok(format!("{} = {}", key, value))
+}
+
+fn render_key_path(tokens: &[TokenTree]) -> Result<String, String> {
+    if tokens.is_empty() {
+        return Err("expected a key before `=`".to_string());
+    }
+
+    let segments = tokens
+        .split(|token| is_punct(token, '.'))
+        .map(render_key_segment)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(segments.join("."))
+}
+
+fn render_key_segment(tokens: &[TokenTree]) -> Result<String, String> {
+    let invalid = || format!("invalid key `{}`", to_source(tokens));
+
+    if let [TokenTree::Literal(literal)] = tokens {
+        return parse_string(&literal.to_string())
+            .map(|key| toml_string(&key))
+            .ok_or_else(invalid);
+    }
+
+    let mut key = String::new();
+    let mut expect_part = true;
+
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) if expect_part => {
+                let ident = ident.to_string();
+                key.push_str(ident.trim_start_matches("r#"));
+            }
+            TokenTree::Literal(literal) if expect_part => {
+                let literal = literal.to_string();
+                if !literal.chars().all(|ch| ch.is_ascii_digit()) {
+                    return Err(invalid());
+                }
+                key.push_str(&literal);
+            }
+            TokenTree::Punct(punct) if !expect_part && punct.as_char() == '-' => key.push('-'),
+            _ => return Err(invalid()),
+        }
+
+        expect_part = !expect_part;
+    }
+
+    if expect_part {
+        return Err(invalid());
+    }
+
+    Ok(key)
+}
+
+fn render_value(tokens: &[TokenTree]) -> Result<String, String> {
+    match tokens {
+        [TokenTree::Literal(literal)] => render_literal(&literal.to_string()),
+
+        [TokenTree::Punct(sign), TokenTree::Literal(literal)]
+            if sign.as_char() == '-' || sign.as_char() == '+' =>
+        {
+            let literal = literal.to_string();
+            if literal.starts_with(|ch: char| ch.is_ascii_digit()) {
+                render_literal(&literal).map(|number| format!("{}{}", sign.as_char(), number))
+            } else {
+                Err(format!("expected a number after `{}`", sign.as_char()))
+            }
+        }
+
+        [TokenTree::Ident(ident)]
+            if ident.to_string() == "true" || ident.to_string() == "false" =>
+        {
+            Ok(ident.to_string())
+        }
+
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::Bracket => {
+            let tokens = flatten(group.stream());
+            let items = split_commas(&tokens)
+                .map(render_value)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(format!("[{}]", items.join(", ")))
+        }
+
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::Brace => {
+            let tokens = flatten(group.stream());
+            let pairs = split_commas(&tokens)
+                .map(render_pair)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if pairs.is_empty() {
+                Ok("{}".to_string())
+            } else {
+                Ok(format!("{{ {} }}", pairs.join(", ")))
+            }
+        }
+
+        [] => Err("expected a value after `=`".to_string()),
+
+        _ => Err(format!("unsupported value `{}`", to_source(tokens))),
+    }
+}
+
+// Splits a comma-separated list, allowing a trailing comma.
+fn split_commas(tokens: &[TokenTree]) -> impl Iterator<Item = &[TokenTree]> {
+    let trailing = tokens.last().map_or(true, |token| is_punct(token, ','));
+    let len = tokens.split(|token| is_punct(token, ',')).count();
+
+    tokens
+        .split(|token| is_punct(token, ','))
+        .take(if trailing { len - 1 } else { len })
+}
+
+fn render_literal(literal: &str) -> Result<String, String> {
+    if let Some(value) = parse_string(literal) {
+        return Ok(toml_string(&value));
+    }
+
+    let is_number = match literal.strip_prefix("0x") {
+        Some(digits) => digits.chars().all(|ch| ch.is_ascii_hexdigit() || ch == '_'),
+        None => {
+            let digits = literal
+                .strip_prefix("0o")
+                .or_else(|| literal.strip_prefix("0b"))
+                .unwrap_or(literal);
+
+            literal.starts_with(|ch: char| ch.is_ascii_digit())
+                && digits
+                    .chars()
+                    .all(|ch| ch.is_ascii_digit() || "_.eE+-".contains(ch))
+        }
+    };
+
+    if is_number {
+        Ok(literal.to_string())
+    } else {
+        Err(format!("unsupported literal `{}`", literal))
+    }
+}
+
+// Returns the value of a string literal, given its source representation.
+fn parse_string(literal: &str) -> Option<String> {
+    if let Some(raw) = literal.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        let content = raw.get(hashes + 1..raw.len().checked_sub(hashes + 1)?)?;
+        return Some(content.to_string());
+    }
+
+    let content = literal.strip_prefix('"')?.strip_suffix('"')?;
+    let mut value = String::with_capacity(content.len());
+    let mut chars = content.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            value.push(ch);
+            continue;
+        }
+
+        match chars.next()? {
+            'n' => value.push('\n'),
+            'r' => value.push('\r'),
+            't' => value.push('\t'),
+            '0' => value.push('\0'),
+            '\\' => value.push('\\'),
+            '\'' => value.push('\''),
+            '"' => value.push('"'),
+            'x' => {
+                let code: String = chars.by_ref().take(2).collect();
+                value.push(u8::from_str_radix(&code, 16).ok()? as char);
+            }
+            'u' => {
+                let code: String = chars.by_ref().skip(1).take_while(|ch| *ch != '}').collect();
+                value.push(char::from_u32(u32::from_str_radix(&code, 16).ok()?)?);
+            }
+            '\n' => {
+                let rest = chars.as_str().trim_start();
+                chars = rest.chars();
+            }
+            _ => return None,
+        }
+    }
+
+    Some(value)
+}
+
+// Renders a TOML string. Values containing quotes or backslashes, such as
+// Windows paths, are rendered as literal strings when possible, which don't
+// need them to be escaped.
+fn toml_string(value: &str) -> String {
+    let needs_escape = value.contains(|ch| ch == '"' || ch == '\\');
+    let is_literal = !value.contains(|ch: char| ch == '\'' || ch.is_control());
+
+    if needs_escape && is_literal {
+        format!("'{}'", value)
+    } else {
+        basic_string(value)
+    }
+}
+
+// Renders a TOML basic string, escaping what the TOML specification
+// requires.
+fn basic_string(value: &str) -> String {
+    let mut rendered = String::with_capacity(value.len() + 2);
+    rendered.push('"');
+
+    for ch in value.chars() {
+        match ch {
+            '"' => rendered.push_str("\\\""),
+            '\\' => rendered.push_str("\\\\"),
+            '\n' => rendered.push_str("\\n"),
+            '\r' => rendered.push_str("\\r"),
+            '\t' => rendered.push_str("\\t"),
+            '\u{8}' => rendered.push_str("\\b"),
+            '\u{c}' => rendered.push_str("\\f"),
+            ch if ch.is_control() && (ch as u32) < 0x80 => {
+                rendered.push_str(&format!("\\u{:04X}", ch as u32))
+            }
+            ch => rendered.push(ch),
+        }
+    }
+
+    rendered.push('"');
+    rendered
+}
+
+fn to_source(tokens: &[TokenTree]) -> String {
+    tokens.iter().cloned().collect::<TokenStream>().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feature_table_from_manifest() {
+        let manifest = Manifest::parse(
+            r#"
+[features]
+default = ["std"]
+## Uses the standard library.
+std = ["alloc"]
+## Uses `alloc`, for targets
+## without an operating system.
+alloc = []
+## Serializes with `serde` | `miniserde`.
+serde = ["dep:serde"]
+"#,
+        )
+        .unwrap();
+
+        let left = feature_table(&manifest);
+        let right = "| Feature | Default | Description |\n\
+            |---|---|---|\n\
+            | `std` | yes | Uses the standard library. |\n\
+            | `alloc` | yes | Uses `alloc`, for targets without an operating system. |\n\
+            | `serde` | no | Serializes with `serde` \\| `miniserde`. |";
+
+        assert_eq!(left, right);
+    }
+
+    #[test]
+    fn insertion_points() {
+        let docs = [
+            Some(" A fancy client.".to_string()),
+            None,
+            Some("".to_string()),
+            Some(" It connects to a fancy server.".to_string()),
+        ];
+
+        assert_eq!(insertion_point(&docs, Position::Start), (0, false, true));
+        assert_eq!(
+            insertion_point(&docs, Position::AfterFirstParagraph),
+            (2, true, false)
+        );
+        assert_eq!(insertion_point(&docs, Position::End), (4, true, false));
+        assert_eq!(insertion_point(&[None], Position::End), (1, false, false));
+    }
+
+    #[test]
+    fn comment_texts() {
+        assert_eq!(check_comment_text("rev_note", "pending\tupstream"), Ok(()));
+        assert_eq!(
+            check_comment_text("rev_note", "a\nb"),
+            Err("`rev_note` can't contain control characters, found `\\n`".to_string())
+        );
+        assert!(check_comment_text("comment", "a\rb").is_err());
+    }
+
+    #[test]
+    fn comment_placeholders() {
+        let allowed = ["version", "msrv"];
+        let left = placeholders(
+            "Requires Rust {msrv}, see { version = \"{version}\" }",
+            &allowed,
+        )
+        .unwrap();
+        let right = [
+            Piece::Text("Requires Rust "),
+            Piece::Placeholder("msrv"),
+            Piece::Text(", see { version = \""),
+            Piece::Placeholder("version"),
+            Piece::Text("\" }"),
+        ];
+
+        assert_eq!(left, right);
+        assert_eq!(
+            placeholders("{rust}", &allowed),
+            Err("unknown placeholder `{rust}`, expected `{version}` or `{msrv}`".to_string())
+        );
+    }
+
+    #[test]
+    fn html_escapes() {
+        let left = escape_html("a <b> & \"c\"");
+        let right = "a &lt;b&gt; &amp; \"c\"";
+
+        assert_eq!(left, right);
+    }
+
+    #[test]
+    fn prerelease_versions() {
+        assert!(is_prerelease("2.0.0-rc.1"));
+        assert!(is_prerelease("1.0.0+20211025"));
+        assert!(!is_prerelease("1.13.0"));
+    }
+
+    #[test]
+    fn feature_descriptions_from_manifest() {
+        let manifest = Manifest::parse(
+            r#"
+[dependencies]
+serde = { version = "1", optional = true }
+
+[features]
+## Uses the standard library.
+std = []
+"#,
+        )
+        .unwrap();
+
+        let left = feature_descriptions_table(&manifest, &["serde".into(), "std".into()]);
+        let right = "| Feature | Description |\n\
+            |---|---|\n\
+            | `serde` |  |\n\
+            | `std` | Uses the standard library. |";
+
+        assert_eq!(left.as_deref(), Ok(right));
+        assert!(feature_descriptions_table(&manifest, &["alloc".into()]).is_err());
+    }
+
+    #[test]
+    fn integrations_table_from_manifest() {
+        let manifest = Manifest::parse(
+            r#"
+[dependencies]
+serde = { version = "1.0.100", optional = true }
+tokio = { version = "1.5", optional = true }
+yaml = { package = "serde_yaml", version = "0.8", optional = true }
+log = "0.4"
+
+[dependencies.local]
+path = "../local"
+optional = true
+
+[features]
+default = ["serde"]
+async = ["dep:tokio", "serde?/std"]
+"#,
+        )
+        .unwrap();
+
+        let left = integrations_table(&manifest);
+        let right = "| Feature | Crate | Version |\n\
+            |---|---|---|\n\
+            | `serde`, `async` | [`serde`](https://crates.io/crates/serde) | 1.0.100 |\n\
+            | `async` | [`tokio`](https://crates.io/crates/tokio) | 1.5 |\n\
+            | `yaml` | [`serde_yaml`](https://crates.io/crates/serde_yaml) | 0.8 |\n\
+            | `local` | [`local`](https://crates.io/crates/local) | * |";
+
+        assert_eq!(left, right);
+    }
+
+    #[test]
+    fn example_snippets() {
+        let manifest = Manifest::parse(
+            r#"
+[[example]]
+name = "server"
+required-features = ["tokio", "tls"]
+
+[[example]]
+name = "client"
+"#,
+        )
+        .unwrap();
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+        let left = example_snippet(&manifest, dir, "server");
+        let right = "```console\n$ cargo run --example server --features tokio,tls\n```\n\n\
+            ```TOML\n[[example]]\nname = \"server\"\nrequired-features = [\"tokio\", \"tls\"]\n```";
+
+        assert_eq!(left.as_deref(), Ok(right));
+        assert_eq!(
+            example_snippet(&manifest, dir, "client").as_deref(),
+            Ok("```console\n$ cargo run --example client\n```")
+        );
+        assert!(example_snippet(&manifest, dir, "missing").is_err());
+    }
+
+    #[test]
+    fn paths_between_directories() {
+        let root = Path::new("/repo");
+
+        assert_eq!(
+            path_between(root, Path::new("/repo/crates/fancy")),
+            "crates/fancy"
+        );
+        assert_eq!(
+            path_between(&root.join("apps/server"), Path::new("/repo/crates/fancy")),
+            "../../crates/fancy"
+        );
+        assert_eq!(
+            path_between(
+                &root.join("./crates/../apps"),
+                Path::new("/repo/crates/fancy")
+            ),
+            "../crates/fancy"
+        );
+        assert_eq!(path_between(root, root), ".");
+    }
+
+    #[test]
+    fn toml_strings() {
+        assert_eq!(toml_string("1.13.0"), r#""1.13.0""#);
+        assert_eq!(toml_string(r"C:\Users\fancy"), r"'C:\Users\fancy'");
+        assert_eq!(toml_string(r#"say "hi""#), r#"'say "hi"'"#);
+        assert_eq!(toml_string(r"it's C:\"), r#""it's C:\\""#);
+        assert_eq!(toml_string("a\\\nb"), r#""a\\\nb""#);
+        assert_eq!(
+            toml_string("https://example.com/?q=\"a b\"&x=1"),
+            r#"'https://example.com/?q="a b"&x=1'"#
+        );
+    }
+
+    #[test]
+    fn enabled_features_from_environment() {
+        let manifest = Manifest::parse(
+            r#"
+[dependencies]
+serde = { version = "1", optional = true }
+
+[features]
+default = ["std"]
+std = []
+full = ["serde"]
+internal-testing = []
+"#,
+        )
+        .unwrap();
+        let active = ["STD", "FULL", "SERDE", "INTERNAL_TESTING"]
+            .iter()
+            .map(|feature| feature.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            enabled_features_of(&manifest, &active, &[]),
+            ["full", "internal-testing", "serde"]
+        );
+        assert_eq!(
+            enabled_features_of(
+                &manifest,
+                &active,
+                &["internal-testing".into(), "serde".into()]
+            ),
+            ["full"]
+        );
+    }
+
+    #[test]
+    fn rust_version_from_manifest() {
+        let manifest =
+            Manifest::parse("[package]\nname = \"fancy\"\nrust-version = \"1.54\"\n").unwrap();
+
+        assert_eq!(manifest_rust_version(&manifest).as_deref(), Some("1.54"));
+        assert_eq!(manifest_rust_version(&Manifest::parse("").unwrap()), None);
+    }
+
+    #[test]
+    fn normalize_url_spellings() {
+        let right = "github.com/scrabsha/dep-doc";
+
+        assert_eq!(normalize_url("https://github.com/scrabsha/dep-doc"), right);
+        assert_eq!(
+            normalize_url("https://www.GitHub.com/scrabsha/dep-doc/"),
+            right
+        );
+        assert_eq!(
+            normalize_url("https://github.com/scrabsha/dep-doc.git"),
+            right
+        );
+        assert_eq!(normalize_url("git@github.com:scrabsha/dep-doc.git"), right);
+        assert_eq!(
+            normalize_url("ssh://git@github.com/scrabsha/dep-doc"),
+            right
+        );
+    }
+
+    #[test]
+    fn normalize_url_forks() {
+        assert_ne!(
+            normalize_url("https://github.com/someone/dep-doc"),
+            normalize_url("https://github.com/scrabsha/dep-doc"),
+        );
+    }
+}
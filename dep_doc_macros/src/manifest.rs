@@ -0,0 +1,313 @@
+// Reads the parts of `Cargo.toml` dep_doc cares about, with the TOML reader
+// shared with `dep_doc`.
+
+use dep_doc_toml::{Table, Value};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+pub(crate) use dep_doc_toml::Entry;
+
+pub(crate) struct Manifest {
+    pub(crate) tables: Vec<Table>,
+}
+
+impl Manifest {
+    // Reads the manifest of the crate being compiled.
+    pub(crate) fn read() -> Result<Manifest, String> {
+        Manifest::read_from(&manifest_dir()?)
+    }
+
+    // Reads the manifest placed in `dir`.
+    pub(crate) fn read_from(dir: &Path) -> Result<Manifest, String> {
+        let path = dir.join("Cargo.toml");
+
+        fs::read_to_string(&path)
+            .map_err(|e| format!("failed to read `{}`: {}", path.display(), e))
+            .and_then(|source| {
+                Manifest::parse(&source)
+                    .map_err(|e| format!("failed to parse `{}`: {}", path.display(), e))
+            })
+    }
+
+    pub(crate) fn parse(source: &str) -> Result<Manifest, String> {
+        dep_doc_toml::tables(source).map(|tables| Manifest { tables })
+    }
+
+    pub(crate) fn table(&self, header: &str) -> Option<&Table> {
+        self.tables.iter().find(|table| table.header == header)
+    }
+
+    // Returns the value of `key` in the `header` table.
+    pub(crate) fn value(&self, header: &str, key: &str) -> Option<&Value> {
+        self.table(header)?
+            .entries
+            .iter()
+            .find(|entry| entry.key == key)
+            .map(|entry| &entry.value)
+    }
+
+    // Returns the features of the crate, including the implicit features of
+    // the optional dependencies which are never referred to as `dep:name`.
+    pub(crate) fn features(&self) -> Vec<String> {
+        let explicit = self.table("features").map_or(&[][..], |t| &t.entries);
+        let mut features = explicit
+            .iter()
+            .map(|entry| entry.key.clone())
+            .collect::<Vec<_>>();
+
+        for name in self.optional_dependencies() {
+            let marker = format!("dep:{}", name);
+            let is_hidden = explicit
+                .iter()
+                .any(|entry| string_array(&entry.value).contains(&marker));
+
+            if !is_hidden && !features.contains(&name) {
+                features.push(name);
+            }
+        }
+
+        features
+    }
+
+    fn optional_dependencies(&self) -> Vec<String> {
+        self.optional_dependency_specs()
+            .into_iter()
+            .map(|dependency| dependency.name)
+            .collect()
+    }
+
+    // Returns the optional dependencies, with the crate and the version
+    // requirement they refer to.
+    pub(crate) fn optional_dependency_specs(&self) -> Vec<Dependency> {
+        self.dependencies()
+            .into_iter()
+            .filter(|(_, fields)| {
+                fields
+                    .iter()
+                    .any(|(key, value)| key == "optional" && *value == Value::Bool(true))
+            })
+            .map(|(name, fields)| Dependency::new(&name, &fields))
+            .collect()
+    }
+
+    // Returns the fields of each dependency, whether it is declared with an
+    // inline table, with dotted keys such as `serde.optional = true`, or with
+    // a `[dependencies.name]` table.
+    fn dependencies(&self) -> Vec<(String, Vec<(String, Value)>)> {
+        let mut dependencies = Vec::<(String, Vec<(String, Value)>)>::new();
+        let mut add = |name: &str, fields: Vec<(String, Value)>| match dependencies
+            .iter_mut()
+            .find(|(other, _)| other == name)
+        {
+            Some((_, other)) => other.extend(fields),
+            None => dependencies.push((name.to_string(), fields)),
+        };
+
+        for table in &self.tables {
+            if is_dependency_table(&table.header) {
+                for entry in &table.entries {
+                    match entry.key.find('.') {
+                        Some(dot) => add(
+                            &entry.key[..dot],
+                            vec![(entry.key[dot + 1..].to_string(), entry.value.clone())],
+                        ),
+                        None => add(&entry.key, fields(&entry.value)),
+                    }
+                }
+            } else if let Some(name) = dependency_table_name(&table.header) {
+                add(
+                    name,
+                    table
+                        .entries
+                        .iter()
+                        .map(|entry| (entry.key.clone(), entry.value.clone()))
+                        .collect(),
+                );
+            }
+        }
+
+        dependencies
+    }
+}
+
+pub(crate) struct Dependency {
+    pub(crate) name: String,
+    pub(crate) package: String,
+    pub(crate) version: Option<String>,
+}
+
+impl Dependency {
+    fn new(name: &str, fields: &[(String, Value)]) -> Dependency {
+        let field = |key: &str| {
+            fields
+                .iter()
+                .find(|(field, _)| field == key)
+                .and_then(|(_, value)| string_array(value).into_iter().next())
+        };
+
+        Dependency {
+            name: name.to_string(),
+            package: field("package").unwrap_or_else(|| name.to_string()),
+            version: field("version"),
+        }
+    }
+}
+
+// Returns the fields of a dependency declared as `name = value`. A string is
+// the version requirement.
+fn fields(value: &Value) -> Vec<(String, Value)> {
+    match value {
+        Value::String(_) => vec![("version".to_string(), value.clone())],
+        Value::Table(fields) => fields.clone(),
+        _ => Vec::new(),
+    }
+}
+
+// Returns the directory containing the manifest of the crate being compiled.
+pub(crate) fn manifest_dir() -> Result<PathBuf, String> {
+    env::var("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .map_err(|_| "`CARGO_MANIFEST_DIR` is not set".to_string())
+}
+
+// Returns the strings of a value, such as `["std", "dep:serde"]`. A string is
+// returned as a single item.
+pub(crate) fn string_array(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(value) => vec![value.clone()],
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|item| match item {
+                Value::String(item) => Some(item.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn is_dependency_table(header: &str) -> bool {
+    ["dependencies", "build-dependencies"]
+        .iter()
+        .any(|table| header == *table || header.ends_with(&format!(".{}", table)))
+}
+
+// Returns `name` for `[dependencies.name]` tables.
+fn dependency_table_name(header: &str) -> Option<&str> {
+    let dot = header.rfind('.')?;
+    if is_dependency_table(&header[..dot]) {
+        Some(&header[dot + 1..])
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+[package]
+name = "fancy"
+description = """
+A crate with [brackets] and { braces },
+serde = { optional = true }
+"""
+
+[dependencies]
+serde = { version = "1", optional = true }
+tokio = { version = "1", optional = true } # referred to with `dep:`
+log = "0.4"
+regex.version = "1"
+regex.optional = true
+anyhow = { workspace = true, optional = true }
+url = { version = "2", features = ["serde"] }
+
+[dependencies.rayon]
+version = "1"
+optional = true
+
+[features]
+## Enables everything.
+default = [
+    "std", # a comment with [brackets]
+    "serde",
+]
+std = []
+async = ["dep:tokio"]
+## Adds notes, spanning
+## two lines.
+note = []
+"#;
+
+    #[test]
+    fn features() {
+        let manifest = Manifest::parse(MANIFEST).unwrap();
+        let left = manifest.features();
+        let right = [
+            "default", "std", "async", "note", "serde", "regex", "anyhow", "rayon",
+        ];
+
+        assert_eq!(left, right);
+    }
+
+    #[test]
+    fn optional_dependency_specs() {
+        let manifest = Manifest::parse(MANIFEST).unwrap();
+        let left = manifest
+            .optional_dependency_specs()
+            .into_iter()
+            .map(|dependency| (dependency.name, dependency.package, dependency.version))
+            .collect::<Vec<_>>();
+        let right = [
+            (
+                "serde".to_string(),
+                "serde".to_string(),
+                Some("1".to_string()),
+            ),
+            (
+                "tokio".to_string(),
+                "tokio".to_string(),
+                Some("1".to_string()),
+            ),
+            (
+                "regex".to_string(),
+                "regex".to_string(),
+                Some("1".to_string()),
+            ),
+            ("anyhow".to_string(), "anyhow".to_string(), None),
+            (
+                "rayon".to_string(),
+                "rayon".to_string(),
+                Some("1".to_string()),
+            ),
+        ];
+
+        assert_eq!(left, right);
+    }
+
+    #[test]
+    fn entries() {
+        let manifest = Manifest::parse(MANIFEST).unwrap();
+        let default = &manifest.table("features").unwrap().entries[0];
+
+        assert_eq!(default.key, "default");
+        assert_eq!(default.docs, ["Enables everything."]);
+        assert_eq!(string_array(&default.value), ["std", "serde"]);
+
+        let note = &manifest.table("features").unwrap().entries[3];
+        assert_eq!(note.docs, ["Adds notes, spanning", "two lines."]);
+    }
+
+    #[test]
+    fn invalid_manifest() {
+        assert_eq!(
+            Manifest::parse("[package]\nname = \"fancy")
+                .err()
+                .as_deref(),
+            Some("line 2: unterminated string")
+        );
+    }
+}
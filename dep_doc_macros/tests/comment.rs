@@ -0,0 +1,35 @@
+use dep_doc_macros::comment;
+
+#[test]
+fn with_placeholders() {
+    let left = comment!([
+        "  ",
+        "Requires Rust {msrv} or newer\n\nSince {version}",
+        ["1.2.3"],
+        [concat!("1.", "54")]
+    ]);
+    let right = "  # Requires Rust 1.54 or newer\n  #\n  # Since 1.2.3\n";
+
+    assert_eq!(left, right);
+}
+
+#[test]
+fn unused_placeholders() {
+    let left = comment!([
+        "",
+        "Optional",
+        [env!("CARGO_PKG_VERSION")],
+        [env!("UNDEFINED_RUST_VERSION")]
+    ]);
+    let right = "# Optional\n";
+
+    assert_eq!(left, right);
+}
+
+#[test]
+fn single_line_text() {
+    let left = dep_doc_macros::comment_text!(["rev_note", "pending\tupstream release"]);
+    let right = "pending\tupstream release";
+
+    assert_eq!(left, right);
+}
@@ -0,0 +1,9 @@
+use dep_doc_macros::deny_prerelease;
+
+deny_prerelease!([]);
+deny_prerelease!(["DEP_DOC_RELEASE_BUILD"]);
+
+#[test]
+fn release_version() {
+    deny_prerelease!([]);
+}
@@ -0,0 +1,17 @@
+use dep_doc_macros::docsrs_select;
+
+#[test]
+fn local_branch() {
+    let left = docsrs_select!([docsrs => "published", local => concat!("from ", "git")]);
+    let right = "from git";
+
+    assert_eq!(left, right);
+}
+
+#[test]
+fn branch_order() {
+    let left = docsrs_select!([local => 1, docsrs => 2]);
+    let right = 1;
+
+    assert_eq!(left, right);
+}
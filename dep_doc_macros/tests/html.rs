@@ -0,0 +1,9 @@
+use dep_doc_macros::html;
+
+#[test]
+fn escapes_literals() {
+    let left = html!([concat!("tokio = \"1\"\n", concat!("# a <b> & c"))]);
+    let right = "<div class=\"dep-doc\"><pre>tokio = \"1\"\n# a &lt;b&gt; &amp; c</pre></div>";
+
+    assert_eq!(left, right);
+}
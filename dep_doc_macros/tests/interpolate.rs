@@ -0,0 +1,27 @@
+use dep_doc_macros::interpolate;
+
+#[test]
+fn with_placeholders() {
+    let left = interpolate!([
+        "Since {name} {version}, see {repository} or `{ key = 1 }`",
+        ["fancy"],
+        [concat!("1.", "2")],
+        ["https://github.com/scrabsha/dep-doc"]
+    ]);
+    let right = "Since fancy 1.2, see https://github.com/scrabsha/dep-doc or `{ key = 1 }`";
+
+    assert_eq!(left, right);
+}
+
+#[test]
+fn unused_placeholders() {
+    let left = interpolate!([
+        "The builder API is stable",
+        [env!("CARGO_PKG_NAME")],
+        [env!("CARGO_PKG_VERSION")],
+        [env!("UNDEFINED_REPOSITORY")]
+    ]);
+    let right = "The builder API is stable";
+
+    assert_eq!(left, right);
+}
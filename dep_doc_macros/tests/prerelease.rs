@@ -0,0 +1,25 @@
+use dep_doc_macros::prerelease;
+
+#[test]
+fn pin_prerelease() {
+    let left = prerelease!([pin, ["rc.1"], concat!("2.0.0-", "rc.1")]);
+    let right = "=2.0.0-rc.1";
+
+    assert_eq!(left, right);
+}
+
+#[test]
+fn pin_release() {
+    let left = prerelease!([pin, [env!("CARGO_PKG_VERSION_PRE")], "0.1.1"]);
+    let right = "0.1.1";
+
+    assert_eq!(left, right);
+}
+
+#[test]
+fn deny_release() {
+    let left = prerelease!([deny, [""], "2.0.0"]);
+    let right = "2.0.0";
+
+    assert_eq!(left, right);
+}
@@ -0,0 +1,11 @@
+use dep_doc_macros::relative_path;
+
+#[test]
+fn from_workspace_root() {
+    assert_eq!(relative_path!([]), "dep_doc_macros");
+}
+
+#[test]
+fn from_base() {
+    assert_eq!(relative_path!(["src"]), "../dep_doc_macros");
+}
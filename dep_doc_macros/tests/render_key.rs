@@ -0,0 +1,83 @@
+use dep_doc_macros::render_key;
+
+#[test]
+fn hyphenated_key() {
+    let left = render_key!(default - features = false);
+    let right = "default-features = false";
+
+    assert_eq!(left, right);
+}
+
+#[test]
+fn dotted_and_quoted_keys() {
+    let left = render_key!(target."cfg(unix)".version = "1");
+    let right = "target.\"cfg(unix)\".version = \"1\"";
+
+    assert_eq!(left, right);
+}
+
+#[test]
+fn array() {
+    let left = render_key!(features = ["a", "b",]);
+    let right = "features = [\"a\", \"b\"]";
+
+    assert_eq!(left, right);
+}
+
+#[test]
+fn inline_table() {
+    let left = render_key!(dep = { version = "1", default-features = false });
+    let right = "dep = { version = \"1\", default-features = false }";
+
+    assert_eq!(left, right);
+}
+
+#[test]
+fn numbers() {
+    let left = render_key!(numbers = [-1, 1_000, 0x2a, 1.5e-3]);
+    let right = "numbers = [-1, 1_000, 0x2a, 1.5e-3]";
+
+    assert_eq!(left, right);
+}
+
+#[test]
+fn string_escapes() {
+    let left = render_key!(comment = r#"a "quoted" \ path"#);
+    let right = r#"comment = 'a "quoted" \ path'"#;
+
+    assert_eq!(left, right);
+
+    let left = render_key!(comment = "tab\there\u{1}");
+    let right = r#"comment = "tab\there\u0001""#;
+
+    assert_eq!(left, right);
+}
+
+#[test]
+fn windows_path() {
+    let left = render_key!(path = "C:\\Users\\fancy\\crates\\dep");
+    let right = r"path = 'C:\Users\fancy\crates\dep'";
+
+    assert_eq!(left, right);
+}
+
+#[test]
+fn url_with_special_characters() {
+    let left = render_key!(git = "https://example.com/it's/\"repo\"");
+    let right = r#"git = "https://example.com/it's/\"repo\"""#;
+
+    assert_eq!(left, right);
+}
+
+#[test]
+fn string_expression() {
+    let left = render_key!(@string "path" = "C:\\fancy");
+    let right = r"path = 'C:\fancy'";
+
+    assert_eq!(left, right);
+
+    let left = render_key!(@string "version" = concat!("1.", "0"));
+    let right = r#"version = "1.0""#;
+
+    assert_eq!(left, right);
+}
@@ -0,0 +1,18 @@
+use dep_doc_macros::workspace_members_doc;
+
+#[test]
+fn own_workspace() {
+    let left = workspace_members_doc!();
+    let right = concat!(
+        "| Crate | Version |\n|---|---|\n",
+        "| [`dep_doc`](https://crates.io/crates/dep_doc) | ",
+        env!("CARGO_PKG_VERSION"),
+        " |\n| [`dep_doc_macros`](https://crates.io/crates/dep_doc_macros) | ",
+        env!("CARGO_PKG_VERSION"),
+        " |\n| [`dep_doc_toml`](https://crates.io/crates/dep_doc_toml) | ",
+        env!("CARGO_PKG_VERSION"),
+        " |",
+    );
+
+    assert_eq!(left, right);
+}
@@ -0,0 +1,567 @@
+//! The TOML reader shared by [`dep_doc`] and its procedural macros.
+//!
+//! It reads the subset of TOML used by `Cargo.toml` manifests and by the
+//! snippets: it checks that the document follows the TOML grammar and doesn't
+//! define a key or a table twice, and keeps the values along with the `##`
+//! documentation comments placed right before them. Dates and times are not
+//! supported, as they have no use in a `Cargo.toml`.
+//!
+//! This crate is an implementation detail of `dep_doc`: its API is not
+//! considered public and may change in any release.
+//!
+//! [`dep_doc`]: https://docs.rs/dep_doc
+
+use std::collections::HashSet;
+
+/// Checks that `document` is valid TOML.
+pub fn validate(document: &str) -> Result<(), String> {
+    tables(document).map(drop)
+}
+
+/// Returns the values of the document, along with their full dotted key, such
+/// as `package.name`.
+pub fn values(document: &str) -> Result<Vec<(String, Value)>, String> {
+    let mut values = Vec::new();
+
+    for table in tables(document)? {
+        for entry in table.entries {
+            let path = if table.header.is_empty() {
+                entry.key
+            } else {
+                format!("{}.{}", table.header, entry.key)
+            };
+            values.push((path, entry.value));
+        }
+    }
+
+    Ok(values)
+}
+
+/// Returns the tables of the document, in order. The keys defined before the
+/// first header belong to a first table, whose header is empty.
+pub fn tables(document: &str) -> Result<Vec<Table>, String> {
+    let mut parser = Parser {
+        source: document,
+        pos: 0,
+        tables: vec![Table {
+            header: String::new(),
+            entries: Vec::new(),
+        }],
+    };
+
+    match parser.document() {
+        Ok(()) => Ok(parser.tables),
+        Err(message) => {
+            let line = document[..parser.pos].matches('\n').count() + 1;
+            Err(format!("line {}: {}", line, message))
+        }
+    }
+}
+
+/// A `[header]` or `[[header]]` table, whose dotted header is unquoted, such
+/// as `package.metadata.docs.rs`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Table {
+    pub header: String,
+    pub entries: Vec<Entry>,
+}
+
+/// A `key = value` pair, with the `##` comments placed right before it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub key: String,
+    pub value: Value,
+    pub docs: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    String(String),
+    Bool(bool),
+    Array(Vec<Value>),
+    Table(Vec<(String, Value)>),
+    // Numbers.
+    Other,
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+    tables: Vec<Table>,
+}
+
+impl Parser<'_> {
+    fn document(&mut self) -> Result<(), String> {
+        let mut tables = HashSet::new();
+        let mut keys = HashSet::new();
+        let mut docs = Vec::new();
+
+        loop {
+            self.whitespace();
+
+            match self.peek() {
+                None => return Ok(()),
+                Some('#') => {
+                    if let Some(doc) = self.rest_of_line().strip_prefix("##") {
+                        docs.push(doc.trim().to_string());
+                    }
+                }
+                Some('\n') | Some('\r') => docs.clear(),
+                Some('[') => {
+                    self.pos += 1;
+                    let is_array = self.eat('[');
+                    self.whitespace();
+                    let header = self.key()?.join(".");
+                    self.whitespace();
+                    self.expect(']')?;
+                    if is_array {
+                        self.expect(']')?;
+                    }
+
+                    if !is_array && !tables.insert(header.clone()) {
+                        return Err(format!("table `{}` is defined twice", header));
+                    }
+                    keys.clear();
+                    docs.clear();
+                    self.tables.push(Table {
+                        header,
+                        entries: Vec::new(),
+                    });
+                }
+                Some(_) => {
+                    let key = self.key()?.join(".");
+                    self.whitespace();
+                    self.expect('=')?;
+                    self.whitespace();
+                    let value = self.value()?;
+
+                    if !keys.insert(key.clone()) {
+                        return Err(format!("key `{}` is defined twice", key));
+                    }
+
+                    self.tables.last_mut().unwrap().entries.push(Entry {
+                        key,
+                        value,
+                        docs: std::mem::take(&mut docs),
+                    });
+                }
+            }
+
+            self.whitespace();
+            self.comment()?;
+            if !self.newline() && self.peek().is_some() {
+                return Err("expected a new line".to_string());
+            }
+        }
+    }
+
+    fn key(&mut self) -> Result<Vec<String>, String> {
+        let mut segments = vec![self.key_segment()?];
+
+        loop {
+            let start = self.pos;
+            self.whitespace();
+            if !self.eat('.') {
+                self.pos = start;
+                return Ok(segments);
+            }
+            self.whitespace();
+            segments.push(self.key_segment()?);
+        }
+    }
+
+    fn key_segment(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some('"') => self.basic_string(),
+            Some('\'') => self.literal_string(),
+            _ => {
+                let start = self.pos;
+                while let Some(ch) = self.peek() {
+                    if !(ch.is_ascii_alphanumeric() || ch == '-' || ch == '_') {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+
+                if self.pos == start {
+                    Err("expected a key".to_string())
+                } else {
+                    Ok(self.source[start..self.pos].to_string())
+                }
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        match self.peek() {
+            Some('"') => self.basic_string().map(Value::String),
+            Some('\'') => self.literal_string().map(Value::String),
+            Some('[') => self.array().map(Value::Array),
+            Some('{') => self.inline_table().map(Value::Table),
+            Some(_) => self.scalar(),
+            None => Err("expected a value".to_string()),
+        }
+    }
+
+    fn array(&mut self) -> Result<Vec<Value>, String> {
+        let mut items = Vec::new();
+        self.expect('[')?;
+
+        loop {
+            self.array_whitespace()?;
+            if self.eat(']') {
+                return Ok(items);
+            }
+
+            items.push(self.value()?);
+            self.array_whitespace()?;
+            if self.eat(']') {
+                return Ok(items);
+            }
+            self.expect(',')?;
+        }
+    }
+
+    // Arrays may span several lines, and contain comments.
+    fn array_whitespace(&mut self) -> Result<(), String> {
+        loop {
+            self.whitespace();
+            self.comment()?;
+            if !self.newline() {
+                return Ok(());
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> Result<Vec<(String, Value)>, String> {
+        let mut keys = HashSet::new();
+        let mut fields = Vec::new();
+
+        self.expect('{')?;
+        self.whitespace();
+        if self.eat('}') {
+            return Ok(fields);
+        }
+
+        loop {
+            let key = self.key()?.join(".");
+            self.whitespace();
+            self.expect('=')?;
+            self.whitespace();
+            let value = self.value()?;
+            self.whitespace();
+
+            if !keys.insert(key.clone()) {
+                return Err(format!("key `{}` is defined twice", key));
+            }
+            fields.push((key, value));
+
+            if self.eat('}') {
+                return Ok(fields);
+            }
+            self.expect(',')?;
+            self.whitespace();
+        }
+    }
+
+    fn scalar(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        while let Some(ch) = self.peek() {
+            if !(ch.is_ascii_alphanumeric() || "+-._".contains(ch)) {
+                break;
+            }
+            self.pos += 1;
+        }
+
+        match &self.source[start..self.pos] {
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            scalar if is_number(scalar) => Ok(Value::Other),
+            _ => {
+                self.pos = start;
+                Err(format!("invalid value `{}`", self.rest_of_line()))
+            }
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, String> {
+        let multiline = self.source[self.pos..].starts_with("\"\"\"");
+        self.pos += if multiline { 3 } else { 1 };
+        let mut value = String::new();
+
+        loop {
+            let ch = self.next().ok_or("unterminated string")?;
+            match ch {
+                '"' if !multiline => return Ok(value),
+                '"' if self.source[self.pos..].starts_with("\"\"") => {
+                    self.pos += 2;
+                    return Ok(value);
+                }
+                '\\' => {
+                    let escape = self.next().ok_or("unterminated string")?;
+                    match escape {
+                        'b' => value.push('\u{8}'),
+                        't' => value.push('\t'),
+                        'n' => value.push('\n'),
+                        'f' => value.push('\u{c}'),
+                        'r' => value.push('\r'),
+                        '"' => value.push('"'),
+                        '\\' => value.push('\\'),
+                        'u' | 'U' => {
+                            let len = if escape == 'u' { 4 } else { 8 };
+                            let code = self.source.get(self.pos..self.pos + len);
+                            let ch = code
+                                .filter(|code| code.chars().all(|ch| ch.is_ascii_hexdigit()))
+                                .and_then(|code| u32::from_str_radix(code, 16).ok())
+                                .and_then(char::from_u32)
+                                .ok_or("invalid unicode escape")?;
+                            self.pos += len;
+                            value.push(ch);
+                        }
+                        '\n' | ' ' | '\t' | '\r' if multiline => {
+                            while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+                                self.pos += 1;
+                            }
+                        }
+                        escape => return Err(format!("invalid escape sequence `\\{}`", escape)),
+                    }
+                }
+                '\n' if multiline => value.push(ch),
+                ch if ch.is_control() && ch != '\t' => {
+                    return Err("strings can't contain control characters".to_string())
+                }
+                ch => value.push(ch),
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, String> {
+        let multiline = self.source[self.pos..].starts_with("'''");
+        let delimiter = if multiline { "'''" } else { "'" };
+        self.pos += delimiter.len();
+
+        let len = self.source[self.pos..]
+            .find(delimiter)
+            .ok_or("unterminated string")?;
+        let value = &self.source[self.pos..self.pos + len];
+        if value
+            .chars()
+            .any(|ch| ch.is_control() && ch != '\t' && !(multiline && ch == '\n'))
+        {
+            return Err("strings can't contain control characters".to_string());
+        }
+
+        self.pos += len + delimiter.len();
+        Ok(value.to_string())
+    }
+
+    fn comment(&mut self) -> Result<(), String> {
+        if self.peek() != Some('#') {
+            return Ok(());
+        }
+
+        let comment = self.rest_of_line();
+        if comment.chars().any(|ch| ch.is_control() && ch != '\t') {
+            return Err("comments can't contain control characters".to_string());
+        }
+
+        self.pos += comment.len();
+        Ok(())
+    }
+
+    fn whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn newline(&mut self) -> bool {
+        self.eat('\n')
+            || (self.source[self.pos..].starts_with("\r\n") && self.eat('\r') && self.eat('\n'))
+    }
+
+    fn rest_of_line(&self) -> &str {
+        let rest = &self.source[self.pos..];
+        rest.split(|ch| ch == '\n' || ch == '\r').next().unwrap()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.pos..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos += ch.len_utf8();
+        Some(ch)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            match self.peek() {
+                Some(found) => Err(format!("expected `{}`, found `{}`", expected, found)),
+                None => Err(format!(
+                    "expected `{}`, found the end of the snippet",
+                    expected
+                )),
+            }
+        }
+    }
+}
+
+// Checks an integer or a float, including the `inf` and `nan` special values.
+fn is_number(scalar: &str) -> bool {
+    let unsigned = scalar.strip_prefix(['+', '-'].as_ref()).unwrap_or(scalar);
+    let is_digits = |digits: &str, radix: u32| {
+        !digits.is_empty()
+            && !digits.starts_with('_')
+            && !digits.ends_with('_')
+            && !digits.contains("__")
+            && digits.chars().all(|ch| ch == '_' || ch.is_digit(radix))
+    };
+
+    if unsigned == "inf" || unsigned == "nan" {
+        return true;
+    }
+
+    if scalar == unsigned {
+        for (prefix, radix) in [("0x", 16), ("0o", 8), ("0b", 2)].iter() {
+            if let Some(digits) = scalar.strip_prefix(prefix) {
+                return is_digits(digits, *radix);
+            }
+        }
+    }
+
+    let (mantissa, exponent) = match unsigned.find(['e', 'E'].as_ref()) {
+        Some(idx) => (&unsigned[..idx], Some(&unsigned[idx + 1..])),
+        None => (unsigned, None),
+    };
+    let (integer, fraction) = match mantissa.find('.') {
+        Some(idx) => (&mantissa[..idx], Some(&mantissa[idx + 1..])),
+        None => (mantissa, None),
+    };
+
+    is_digits(integer, 10)
+        && (integer == "0" || !integer.starts_with('0'))
+        && fraction.map_or(true, |fraction| is_digits(fraction, 10))
+        && exponent.map_or(true, |exponent| {
+            is_digits(
+                exponent
+                    .strip_prefix(['+', '-'].as_ref())
+                    .unwrap_or(exponent),
+                10,
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_documents() {
+        let document = r#"# dep-doc-format: v1
+[dependencies]
+tokio = { version = "1.13.0", default-features = false, features = ["rt", 'macros'] }
+"dep.doc" = "0.1"
+serde.version = "1"
+numbers = [-1, 1_000, 0x2a, 1.5e-3, +inf]
+features = [
+    "rt", # comment
+    "macros",
+]
+
+[[example]]
+name = "server"
+
+[[example]]
+name = "client"
+"#;
+
+        assert_eq!(validate(document), Ok(()));
+        assert_eq!(validate("path = 'C:\\Users\\fancy'"), Ok(()));
+        assert_eq!(validate(r#"comment = "tab\there\u0001""#), Ok(()));
+    }
+
+    #[test]
+    fn tables() {
+        let document = r#"name = "root"
+
+[dependencies]
+## The runtime.
+tokio = { version = "1", features = ["rt"] }
+serde.optional = true
+
+[[example]]
+name = "server"
+"#;
+
+        let left = super::tables(document).unwrap();
+        let entry = |key: &str, value: Value, docs: &[&str]| Entry {
+            key: key.to_string(),
+            value,
+            docs: docs.iter().map(|doc| doc.to_string()).collect(),
+        };
+        let right = vec![
+            Table {
+                header: String::new(),
+                entries: vec![entry("name", Value::String("root".to_string()), &[])],
+            },
+            Table {
+                header: "dependencies".to_string(),
+                entries: vec![
+                    entry(
+                        "tokio",
+                        Value::Table(vec![
+                            ("version".to_string(), Value::String("1".to_string())),
+                            (
+                                "features".to_string(),
+                                Value::Array(vec![Value::String("rt".to_string())]),
+                            ),
+                        ]),
+                        &["The runtime."],
+                    ),
+                    entry("serde.optional", Value::Bool(true), &[]),
+                ],
+            },
+            Table {
+                header: "example".to_string(),
+                entries: vec![entry("name", Value::String("server".to_string()), &[])],
+            },
+        ];
+
+        assert_eq!(left, right);
+    }
+
+    #[test]
+    fn invalid_documents() {
+        let cases = [
+            ("tokio = \"1", "line 1: unterminated string"),
+            ("tokio = { version = \"1\", }", "line 1: expected a key"),
+            ("tokio = 1.13.0", "line 1: invalid value `1.13.0`"),
+            ("a = 1\na = 2", "line 2: key `a` is defined twice"),
+            ("[a]\n[a]", "line 2: table `a` is defined twice"),
+            ("tokio = \"\\u{1F600}\"", "line 1: invalid unicode escape"),
+            ("tokio = r\"1\"", "line 1: invalid value `r\"1\"`"),
+            ("tokio = \"1\" features", "line 1: expected a new line"),
+            (
+                "tokio = { version = \"1\",\nfeatures = [] }",
+                "line 1: expected a key",
+            ),
+        ];
+
+        for (document, error) in cases.iter() {
+            assert_eq!(validate(document), Err(error.to_string()), "{}", document);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! `cargo dep-doc`: rewrites the dependency snippet of a README.
+//!
+//! The snippet is placed between a `<!-- dep-doc start -->` and a
+//! `<!-- dep-doc end -->` comment, and is replaced by the one [`dep_doc`]
+//! generates for the crate of the current directory. The options of the
+//! snippet are read from the `[package.metadata.dep-doc]` table of its
+//! manifest. With `--check`, the README is left untouched, and the command
+//! fails if it is out of date.
+//!
+//! [`dep_doc`]: https://docs.rs/dep_doc
+
+use dep_doc::Snippet;
+use std::{env, fs, process};
+
+const START: &str = "<!-- dep-doc start -->";
+const END: &str = "<!-- dep-doc end -->";
+
+const USAGE: &str = "usage: cargo dep-doc [--check] [--readme <path>]";
+
+fn main() {
+    if let Err(message) = run() {
+        eprintln!("error: {}", message);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut check = false;
+    let mut readme = "README.md".to_string();
+
+    // Cargo passes the name of the subcommand as the first argument.
+    let mut args = env::args().skip(1).peekable();
+    if args.peek().map(String::as_str) == Some("dep-doc") {
+        args.next();
+    }
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => check = true,
+            "--readme" => readme = args.next().ok_or(USAGE)?,
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            _ => return Err(format!("unexpected argument `{}`\n\n{}", arg, USAGE)),
+        }
+    }
+
+    let manifest = fs::read_to_string("Cargo.toml")
+        .map_err(|e| format!("failed to read `Cargo.toml`: {}", e))?;
+    let snippet = Snippet::from_manifest(&manifest)
+        .map_err(|e| format!("failed to read `Cargo.toml`: {}", e))?
+        .to_string();
+
+    let content =
+        fs::read_to_string(&readme).map_err(|e| format!("failed to read `{}`: {}", readme, e))?;
+    let updated = replace_snippet(&content, &snippet)?;
+
+    if updated == content {
+        return Ok(());
+    }
+
+    if check {
+        return Err(format!(
+            "`{}` is out of date, run `cargo dep-doc` to update it",
+            readme
+        ));
+    }
+
+    fs::write(&readme, updated).map_err(|e| format!("failed to write `{}`: {}", readme, e))
+}
+
+// Replaces what is between the markers of `content` with `snippet`.
+fn replace_snippet(content: &str, snippet: &str) -> Result<String, String> {
+    let start = content
+        .find(START)
+        .ok_or_else(|| format!("no `{}` marker found", START))?
+        + START.len();
+    let end = content[start..]
+        .find(END)
+        .ok_or_else(|| format!("no `{}` marker found after `{}`", END, START))?
+        + start;
+
+    Ok(format!(
+        "{}\n{}\n{}",
+        &content[..start],
+        snippet,
+        &content[end..]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaced_snippet() {
+        let content = "# Fancy\n\n<!-- dep-doc start -->\n```TOML\n[dependencies]\nfancy = \"0.1.0\"\n```\n<!-- dep-doc end -->\n\nMore\n";
+        let snippet = "```TOML\n[dependencies]\nfancy = \"1.2.3\"\n```";
+
+        let left = replace_snippet(content, snippet);
+        let right = "# Fancy\n\n<!-- dep-doc start -->\n```TOML\n[dependencies]\nfancy = \"1.2.3\"\n```\n<!-- dep-doc end -->\n\nMore\n";
+
+        assert_eq!(left.as_deref(), Ok(right));
+        assert_eq!(replace_snippet(right, snippet).as_deref(), Ok(right));
+        assert!(replace_snippet("# Fancy\n", snippet).is_err());
+    }
+}
@@ -75,10 +75,64 @@
 #![doc = concat!("> ", package_import!(features = ["proc_macro", "no_std"]))]
 //! > ```
 //! > Some other doc
+//!
+//! Similarly, code generation libraries can use [`build_dep_doc`], which
+//! generates a `[build-dependencies]` section.
+//!
+//! # The `proc-macro` feature
+//!
+//! By default, values are rendered with [`stringify`], which may not match the
+//! formatting of a real `Cargo.toml`. Enabling the `proc-macro` feature parses
+//! each key/value pair and serializes it as proper TOML instead: strings are
+//! re-escaped, arrays are written as `["a", "b"]` and inline tables as
+//! `{ key = value }`. Strings containing quotes or backslashes, such as Windows
+//! paths, are written as literal strings (`'C:\deps\fancy'`) when TOML allows
+//! it. Without this feature, string literals are written as they appear in the
+//! source, so raw strings and Rust-specific escapes such as `\u{..}` produce
+//! invalid TOML.
+//!
+//! This feature also enables the checks and macros which read the `Cargo.toml`
+//! of the crate, such as the `verify_features` option of [`dep_doc`] and
+//! [`feature_doc`], as well as the `#[dep_doc::inject]` attribute, which
+//! inserts the snippet in the middle of the documentation of an item.
+//!
+//! # Keeping the README up to date
+//!
+//! The `cli` feature builds the `cargo dep-doc` binary, which rewrites the
+//! snippet placed between a `<!-- dep-doc start -->` and a
+//! `<!-- dep-doc end -->` comment in the README of the crate of the current
+//! directory. With `--check`, it fails instead when the README is out of date,
+//! which can be run in CI:
+//!
+//! ```console
+//! $ cargo install dep_doc --features cli
+//! $ cargo dep-doc --check
+//! ```
+//!
+//! The snippet is the one [`Snippet`] renders. Its options are read from the
+//! `[package.metadata.dep-doc]` table of the manifest:
+//!
+//! ```toml
+//! [package.metadata.dep-doc]
+//! section = "dev-dependencies"
+//! features = ["derive"]
+//! ```
 
 #[doc(hidden)]
 pub use core;
 
+#[cfg(feature = "proc-macro")]
+#[doc(hidden)]
+pub use dep_doc_macros;
+
+#[cfg(feature = "proc-macro")]
+pub use dep_doc_macros::inject;
+
+pub use snippet::Snippet;
+
+mod snippet;
+mod validate;
+
 /// Generates a `Cargo.toml` code snippet showing how to add the current crate
 /// as a dependency.
 ///
@@ -100,13 +154,353 @@ pub use core;
 /// ```rust
 /// #![doc = dep_doc::dep_doc!(git = "https://github.com/scrabsha/dep-doc")]
 /// ```
+///
+/// Tools which parse the generated snippet can ask for a
+/// `# dep-doc-format: v1` comment at the top of the code block by passing
+/// `format_marker`:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(format_marker, features = ["no_std"])]
+/// ```
+///
+/// By default, the snippet starts with the opening code fence. Passing
+/// `leading_blank_line` adds an empty line before it, which separates the
+/// snippet from the preceding text when it is concatenated with it:
+///
+/// ```rust
+/// #![doc = concat!(
+///     "Add this to your `Cargo.toml`:\n",
+///     dep_doc::dep_doc!(leading_blank_line),
+/// )]
+/// ```
+///
+/// Large crates can recommend a minimal set of features with
+/// `recommended_features`. This disables the default features and enables the
+/// recommended ones:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(recommended_features = ["no_std"])]
+/// ```
+///
+/// When the snippet is nested in a Markdown list or block quote, every line of
+/// it, including the code fences, can be indented by a given amount of spaces
+/// (at most 8) with `block_indent`:
+///
+/// ```rust
+/// #![doc = concat!(
+///     "- Add this to your `Cargo.toml`:\n\n",
+///     dep_doc::dep_doc!(block_indent = 4),
+/// )]
+/// ```
+///
+/// `default-features = false` and `optional = true` can be written with the
+/// `no_default_features` and `optional` shorthands:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(no_default_features, optional, features = ["proc-macro"])]
+/// ```
+///
+/// Crates which can be used without the standard library can document it with
+/// `no_std`, which disables the default features. The features which require
+/// the standard library can be listed with `no_std = [...]`, which adds a
+/// comment mentioning them above the dependency:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(no_std = ["std", "alloc"])]
+/// ```
+///
+/// `-sys` crates can mention the native library they link to, as declared by
+/// the `links` key of their manifest:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(links = "git2")]
+/// ```
+///
+/// The version requirement can be written in different styles, with
+/// `style = ...`. For a crate at version `1.13.0`, `caret` (the default)
+/// renders `1.13.0`, `tilde` renders `~1.13`, `exact` renders `=1.13.0` and
+/// `wildcard` renders `1.*`:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(style = tilde)]
+/// ```
+///
+/// The version can also be truncated with `precision = ...`, which can be
+/// `major`, `minor` or `patch`. Pre-release and build metadata suffixes are
+/// only rendered when the version is not truncated. `tilde` defaults to the
+/// `minor` precision and `wildcard` to the `major` one:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(precision = minor)]
+/// ```
+///
+/// Pre-release versions are handled according to `prerelease = ...`. `keep`,
+/// the default, renders them as-is, while `strip` drops the pre-release suffix
+/// so that `2.0.0-rc.1` is rendered as `2.0.0`:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(prerelease = strip)]
+/// ```
+///
+/// Since a caret requirement on a pre-release also matches the later
+/// pre-releases, `pin` renders pre-release versions as exact requirements
+/// (`=2.0.0-rc.1`). Macros can't emit warnings on stable Rust, so `deny`
+/// instead fails the build when the version is a pre-release, which helps
+/// catching documentation published from a release candidate. Both policies
+/// leave regular versions unchanged and require the `proc-macro` feature:
+///
+/// ```rust,ignore
+/// #![doc = dep_doc::dep_doc!(prerelease = pin)]
+/// ```
+///
+/// When the dependency is pinned to a specific `rev`, the reason for it can be
+/// documented with `rev_note`. This adds a comment above the dependency:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(
+///     git = "https://github.com/scrabsha/dep-doc",
+///     rev = "5d3117d",
+///     rev_note = "pending upstream release",
+/// )]
+/// ```
+///
+/// With the `proc-macro` feature, a note spanning several lines is rejected,
+/// as its second line would not be part of the comment:
+///
+#[cfg_attr(feature = "proc-macro", doc = "```rust,compile_fail")]
+#[cfg_attr(not(feature = "proc-macro"), doc = "```rust,ignore")]
+/// #![doc = dep_doc::dep_doc!(
+///     git = "https://github.com/scrabsha/dep-doc",
+///     rev = "5d3117d",
+///     rev_note = "pending upstream\nrelease",
+/// )]
+/// ```
+///
+/// The `branch`, `tag` or `rev` key is always rendered right after `git`, and
+/// only one of them can be specified. When `git` is omitted, it defaults to
+/// the `repository` of the crate:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(branch = "main")]
+/// ```
+///
+/// ```rust,compile_fail
+/// #![doc = dep_doc::dep_doc!(branch = "main", rev = "5d3117d")]
+/// ```
+///
+/// The version requirement can be replaced with `version = "..."`, for
+/// instance to recommend a previous stable line. The `style` and `precision`
+/// options do not apply to it:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(version = "0.1")]
+/// ```
+///
+/// The version requirement is the first key of the dependency by default. It
+/// can be moved after the other keys with `version_position = last`, which
+/// are rendered in the order they are passed:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(
+///     git = "https://github.com/scrabsha/dep-doc",
+///     features = ["proc-macro"],
+///     version_position = last,
+/// )]
+/// ```
+///
+/// Git and path dependencies can omit the version requirement with
+/// `version = false`:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(version = false, git = "https://github.com/scrabsha/dep-doc")]
+/// ```
+///
+/// Crates of a monorepo which are documented as path dependencies can use
+/// `relative_path`, which renders the `path` key with the location of the
+/// crate relative to the root of its workspace, so that it stays correct when
+/// the crate is moved. The path can be made relative to another directory of
+/// the workspace, such as the crate depending on it, with
+/// `relative_path = "..."`. This requires the `proc-macro` feature:
+///
+/// ```rust,ignore
+/// #![doc = dep_doc::dep_doc!(version = false, relative_path)]
+/// ```
+///
+/// Long dependency specifications can be rendered as a table of their own with
+/// `form = table`, instead of the default `form = inline`:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(form = table, features = ["no_std"])]
+/// ```
+///
+/// Long arrays, such as a list of features, can be split with one item per
+/// line with `form = multiline`. TOML requires inline tables to fit on a
+/// single line, so the other keys are kept on the first line:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(form = multiline, features = ["proc_macro", "no_std"])]
+/// ```
+///
+/// When the crate is meant to be imported under a shorter name, `as` renders a
+/// renamed dependency, keeping the published name in the `package` key:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(as = "dd")]
+/// ```
+///
+/// When the crate is meant to be an optional dependency, `optional_feature`
+/// marks the dependency as optional and adds the `[features]` entry of the
+/// user crate which enables it, as [`optional_dep_full_doc`] does. This can be
+/// combined with the other snippet macros, such as [`target_dep_doc`]:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(optional_feature = "docs")]
+/// ```
+///
+/// Crates released in lockstep with the current crate, such as a companion
+/// derive crate, can be listed with `with`. Each of them gets its own line,
+/// with the same version requirement:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(with = ["dep_doc_macros"])]
+/// ```
+///
+/// Dependencies the user crate must add alongside the current crate, such as
+/// a runtime, can be declared with `peer(...)`. Its first argument is the name
+/// of the peer dependency, followed by the keys of its declaration:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(peer("tokio", version = "1", features = ["rt-multi-thread"]))]
+/// ```
+///
+/// A peer accepts the `optional`, `no_default_features` and `no_std` flags as
+/// well, but not the options which apply to the whole snippet:
+///
+/// ```rust,compile_fail
+/// #![doc = dep_doc::dep_doc!(peer("tokio", version = "1", details))]
+/// ```
+///
+/// Lines which dep_doc can't generate, such as a comment or an unrelated
+/// dependency, can be appended to the code block with `raw`. It accepts either
+/// a single line or an array of lines, which are written as-is:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(raw = ["# Needed by the examples:", "serde_json = \"1\""])]
+/// ```
+///
+/// The name and version of the crate are read from the `CARGO_PKG_NAME` and
+/// `CARGO_PKG_VERSION` environment variables. A wrapper crate documenting how
+/// to depend on the crate it wraps can read them from other environment
+/// variables, for instance set by its build script, with `name_env` and
+/// `version_env`. The name must be the package name, as `CARGO_PKG_NAME`,
+/// rather than the name used in Rust code. As the version components are
+/// unknown in this case, the version can't be truncated:
+///
+/// ```rust,ignore
+/// #![doc = dep_doc::dep_doc!(name_env = "INNER_NAME", version_env = "INNER_VERSION")]
+/// ```
+///
+/// The other macros rendering the current crate as a dependency, such as
+/// [`dev_dep_doc`], [`target_dep_doc`] or [`cargo_add_doc`], accept them as
+/// well.
+///
+/// A comment can be added above the dependency with `comment`. With the
+/// `proc-macro` feature, the `{version}` and `{msrv}` placeholders are replaced
+/// by the version and the `rust-version` of the crate. Without it, the comment
+/// is rendered as-is:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(comment = "Enable the `proc-macro` feature for exact TOML")]
+/// ```
+///
+/// With the `proc-macro` feature, each line of a `comment` gets its own `#`,
+/// and `comment`, `rev_note`, `links` and `raw` can't contain other control
+/// characters than tabs, as they would end the line they are written on.
+/// Without it, their text can't be checked, and must fit on a single line.
+///
+/// The sentence introducing the snippet can be generated as well with
+/// `heading`, which adds `` Add [`name`](https://crates.io/crates/name) to your
+/// `Cargo.toml`: `` above the code block:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(heading)]
+/// ```
+///
+/// Long snippets can be collapsed by default with `details`, which wraps the
+/// code block in a `<details>` element. Its summary is `Cargo.toml`, unless
+/// another one is given with `details = "..."`:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(details = "Show the manifest", features = ["proc-macro"])]
+/// ```
+///
+/// The code block is tagged as `TOML` by default. Markdown renderers which
+/// expect another info string, such as a lowercase `toml` or a tag with
+/// attributes, can be given one with `@lang`. The content of the block is not
+/// changed:
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc!(@lang "toml,no_run", features = ["proc-macro"])]
+/// ```
+///
+/// With the `proc-macro` feature, `@html` renders the snippet as an HTML block
+/// instead of a code block, which can be styled by a stylesheet added with
+/// `--html-in-header`. The content is the same, with the `&`, `<` and `>`
+/// characters escaped:
+///
+/// ```rust,ignore
+/// #![doc = dep_doc::dep_doc!(@html, features = ["proc-macro"])]
+/// ```
+///
+/// This renders as:
+///
+/// ```html
+/// <div class="dep-doc"><pre>[dependencies]
+/// dep_doc = { version = "0.1.1", features = ["proc-macro"] }</pre></div>
+/// ```
+///
+/// With the `proc-macro` feature, `enabled_features` adds the features the
+/// documentation is built with to the `features` key, so that building it with
+/// `--features full` renders `features = ["full"]`. The default features are
+/// skipped, as well as the ones listed in `exclude`. Cargo only tells build
+/// scripts which features are enabled, so the build script of the crate must
+/// call [`export_features`]:
+///
+/// ```rust,ignore
+/// #![doc = dep_doc::dep_doc!(enabled_features(exclude = ["internal"]))]
+/// ```
+///
+/// With the `proc-macro` feature enabled, `verify_features` checks at compile
+/// time that every feature passed in `features` or `recommended_features` is
+/// declared in the `Cargo.toml` of the crate:
+///
+/// ```rust,ignore
+/// #![doc = dep_doc::dep_doc!(verify_features, features = ["serde"])]
+/// ```
+///
+/// Similarly, `verify_git` checks that the `git` URL points to the
+/// `repository` declared in the `Cargo.toml` of the crate:
+///
+/// ```rust,ignore
+/// #![doc = dep_doc::dep_doc!(verify_git, git = "https://github.com/scrabsha/dep-doc")]
+/// ```
+///
+/// Keys which Cargo does not understand in a dependency declaration are
+/// rejected:
+///
+/// ```rust,compile_fail
+/// #![doc = dep_doc::dep_doc!(feautres = ["derive"])]
+/// ```
+///
+/// So are unknown flags:
+///
+/// ```rust,compile_fail
+/// #![doc = dep_doc::dep_doc!(features = ["derive"], defualt_features)]
+/// ```
 #[macro_export]
 macro_rules! dep_doc {
     ( $( $tt:tt )* ) => {
-        $crate::dep_doc_inner!(
-            [$crate::core::env!("CARGO_PKG_NAME"), $crate::core::env!("CARGO_PKG_VERSION")],
-            [$($tt)*],
-        )
+        $crate::with_package!(dep_doc_inner [$($tt)*])
     };
 }
 
@@ -115,12 +509,8 @@ macro_rules! dep_doc {
 #[doc(hidden)]
 #[macro_export]
 macro_rules! dep_doc_inner {
-    ( [$name:expr, $version:expr], [ $( $tt:tt )* ] $(,)? ) => {
-        concat!(
-            "```TOML\n[dependencies]\n",
-            $crate::package_import!([$name, $version], [ $($tt)* ]),
-            "\n```",
-        )
+    ( [$name:expr, $version:expr $(, $components:tt)?], [ $( $tt:tt )* ] $(,)? ) => {
+        $crate::section_inner!([$name, $version $(, $components)?], "dependencies", [ $($tt)* ])
     };
 }
 
@@ -143,10 +533,7 @@ macro_rules! dep_doc_inner {
 #[macro_export]
 macro_rules! dev_dep_doc {
     ( $( $tt:tt )* ) => {
-        $crate::dev_dep_doc_inner!(
-            [$crate::core::env!("CARGO_PKG_NAME"), $crate::core::env!("CARGO_PKG_VERSION")],
-            [$($tt)*],
-        )
+        $crate::with_package!(dev_dep_doc_inner [$($tt)*])
     };
 }
 
@@ -155,94 +542,6044 @@ macro_rules! dev_dep_doc {
 #[doc(hidden)]
 #[macro_export]
 macro_rules! dev_dep_doc_inner {
-    ( [$name:expr, $version:expr], [ $( $tt:tt )* ] $(,)? ) => {
-        concat!(
-            "```TOML\n[dev-dependencies]\n",
-            $crate::package_import!([$name, $version], [$($tt)*]),
-            "\n```",
-        )
+    ( [$name:expr, $version:expr $(, $components:tt)?], [ $( $tt:tt )* ] $(,)? ) => {
+        $crate::section_inner!([$name, $version $(, $components)?], "dev-dependencies", [$($tt)*])
     };
 }
 
-#[doc(hidden)]
+/// Generates a `Cargo.toml` code snippet showing how to add the current crate
+/// as a build-dependency.
+///
+/// # Example
+///
+/// The simplest invocation is:
+///
+/// ```rust
+/// #![doc = dep_doc::build_dep_doc!()]
+/// ```
+///
+/// Specific feature, git repository, path can be passed in the macro invocation:
+///
+/// ```rust
+/// #![doc = dep_doc::build_dep_doc!(git = "https://github.com/scrabsha/dep-doc")]
+/// ```
 #[macro_export]
-macro_rules! package_import {
-    ([$name:expr, $version:expr $(,)? ], [] $(,)? ) => {
-        concat!(
-            $name,
-            " = \"",
-            $version,
-            "\"",
-        )
+macro_rules! build_dep_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::with_package!(build_dep_doc_inner [$($tt)*])
     };
+}
 
-    ([$name:expr, $version:expr $(,)? ], [ $( $rest:tt )* ] $(,)? ) => {
-        concat!(
-            $name,
-            " = { version = \"",
-            $version,
-            "\", ",
-            stringify!( $( $rest)* ),
-            " }",
-        )
+// This is just a testable version of `build_dep_doc`, in which we can inject a
+// specific crate name and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! build_dep_doc_inner {
+    ( [$name:expr, $version:expr $(, $components:tt)?], [ $( $tt:tt )* ] $(,)? ) => {
+        $crate::section_inner!([$name, $version $(, $components)?], "build-dependencies", [$($tt)*])
     };
+}
 
+/// Generates a `Cargo.toml` code snippet adding the current crate along with
+/// other dependencies, such as the ones a quick-start example needs.
+///
+/// The current crate is written `self`, optionally followed by the options of
+/// [`dep_doc`] in parentheses. It is followed by the other dependencies,
+/// written `"name" = "version"` or `"name" = { key = value, ... }`, which are
+/// rendered in the same `[dependencies]` table, in the order they are passed.
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::deps_doc!(
+///     self(features = ["proc-macro"]),
+///     "serde" = { version = "1", features = ["derive"] },
+///     "anyhow" = "1",
+/// )]
+/// ```
+///
+/// The options of the other dependencies are limited to the keys of a
+/// dependency declaration, and to the `optional`, `no_default_features` and
+/// `no_std` flags:
+///
+/// ```rust,compile_fail
+/// #![doc = dep_doc::deps_doc!(self, "serde" = { version = "1", heading })]
+/// ```
+#[macro_export]
+macro_rules! deps_doc {
     ( $( $tt:tt )* ) => {
-        $crate::package_import!(
-            [
-                $crate::core::env!("CARGO_PKG_NAME"),
-                $crate::core::env!("CARGO_PKG_VERSION"),
-            ],
-            [ $( $tt )* ]
-        )
+        $crate::with_package!(deps_doc_inner [$($tt)*])
     };
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+// This is just a testable version of `deps_doc`, in which we can inject a
+// specific crate name and version name.
+//
+// The other dependencies are translated into `peer(...)` options.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! deps_doc_inner {
+    (
+        $package:tt,
+        [ self $( ( $( $option:tt )* ) )? $(, $( $rest:tt )* )? ] $(,)?
+    ) => {
+        $crate::deps_doc_inner!(@deps $package [ $( $( $option )* )? ] [] $( $( $rest )* )?)
+    };
 
-    mod dep_doc_inner {
-        use super::*;
+    (
+        @deps $package:tt $options:tt [ $( $peer:tt )* ]
+        $name:literal = { $( $field:tt )* } $(, $( $rest:tt )* )?
+    ) => {
+        $crate::deps_doc_inner!(
+            @deps $package $options [ $( $peer )* [peer($name, $( $field )*)] ] $( $( $rest )* )?
+        )
+    };
 
-        #[test]
-        fn no_additional_tokens() {
-            let left = dep_doc_inner!(["tokio", "1.13.0"], []);
-            let right = "```TOML\n[dependencies]\ntokio = \"1.13.0\"\n```";
+    (
+        @deps $package:tt $options:tt [ $( $peer:tt )* ]
+        $name:literal = $version:literal $(, $( $rest:tt )* )?
+    ) => {
+        $crate::deps_doc_inner!(
+            @deps $package $options [ $( $peer )* [peer($name, version = $version)] ] $( $( $rest )* )?
+        )
+    };
 
-            assert_eq!(left, right);
-        }
+    ( @deps $package:tt [] [ $( [ $( $peer:tt )* ] )* ] ) => {
+        $crate::dep_doc_inner!($package, [ $( $( $peer )* , )* ])
+    };
 
-        #[test]
-        fn with_git_path() {
-            let left = dep_doc_inner!(
-                ["tokio", "1.13.0"],
-                [git = "https://github.com/tokio-rs/tokio"]
-            );
-            let right = "```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", git = \"https://github.com/tokio-rs/tokio\" }\n```";
+    ( @deps $package:tt [ $( $option:tt )+ ] [ $( [ $( $peer:tt )* ] )* ] ) => {
+        $crate::dep_doc_inner!($package, [ $( $option )+ $( , $( $peer )* )* ])
+    };
 
-            assert_eq!(left, right);
-        }
-    }
+    ( @deps $package:tt $options:tt $peers:tt $( $tt:tt )+ ) => {
+        $crate::core::compile_error!(concat!(
+            "expected `\"name\" = \"version\"` or `\"name\" = { ... }`, found `",
+            stringify!($( $tt )+),
+            "`",
+        ))
+    };
+
+    ( $package:tt, [ $( $tt:tt )* ] $(,)? ) => {
+        $crate::core::compile_error!("`deps_doc` expects `self` or `self(...)` first")
+    };
+}
+
+/// Generates a `Cargo.toml` code snippet made of several dependency tables,
+/// such as a `[dependencies]` and a `[dev-dependencies]` one, in the same code
+/// block.
+///
+/// Each table is written `"header" = [...]`, where the header is the name of
+/// the table without the brackets. Its dependencies are written as the ones of
+/// [`deps_doc`]: the current crate is `self`, optionally followed by the
+/// options of [`dep_doc`] in parentheses, and the other dependencies are
+/// `"name" = "version"` or `"name" = { key = value, ... }`. A crate released
+/// along with the current crate, such as its test helpers, can be written
+/// `"name" = self(...)`: it then has the version requirement of the current
+/// crate. Each table can have at most one such entry, which comes first.
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::sections_doc!(
+///     "dependencies" = [self(features = ["proc-macro"]), "serde" = "1"],
+///     "dev-dependencies" = ["dep_doc_macros" = self, "tokio" = { version = "1", features = ["macros"] }],
+/// )]
+/// ```
+#[macro_export]
+macro_rules! sections_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::with_package!(sections_doc_inner [$($tt)*])
+    };
+}
+
+// This is just a testable version of `sections_doc`, in which we can inject a
+// specific crate name and version name.
+//
+// The dependencies other than the current crate are translated into
+// `peer(...)` options.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! sections_doc_inner {
+    ( $package:tt, [ $( $header:literal = [ $( $entry:tt )* ] ),+ $(,)? ] $(,)? ) => {
+        $crate::sections_doc_inner!(@sections $package [ $( [$header [ $( $entry )* ]] )+ ])
+    };
+
+    ( @sections $package:tt [ $first:tt $( $rest:tt )* ] ) => {
+        concat!(
+            "```TOML\n",
+            $crate::sections_doc_inner!(@section $package $first),
+            $(
+                "\n\n",
+                $crate::sections_doc_inner!(@section $package $rest),
+            )*
+            "\n```",
+        )
+    };
+
+    ( @section $package:tt [$header:literal [ $( $entry:tt )* ]] ) => {
+        $crate::sections_doc_inner!(@entries $package $header [] [] $( $entry )*)
+    };
+
+    (
+        @entries $package:tt $header:literal [] []
+        self $( ( $( $option:tt )* ) )? $(, $( $rest:tt )* )?
+    ) => {
+        $crate::sections_doc_inner!(
+            @entries $package $header [$package [ $( $( $option )* )? ]] [] $( $( $rest )* )?
+        )
+    };
+
+    (
+        @entries [$self:expr, $( $version:tt )*] $header:literal [] []
+        $name:literal = self $( ( $( $option:tt )* ) )? $(, $( $rest:tt )* )?
+    ) => {
+        $crate::sections_doc_inner!(
+            @entries [$self, $( $version )*] $header [[$name, $( $version )*] [ $( $( $option )* )? ]] []
+            $( $( $rest )* )?
+        )
+    };
+
+    (
+        @entries $package:tt $header:literal $own:tt $peers:tt
+        $( $name:literal = )? self $( $rest:tt )*
+    ) => {
+        $crate::core::compile_error!(concat!(
+            "the current crate must be the first dependency of `[",
+            $header,
+            "]`, and can only appear once",
+        ))
+    };
+
+    (
+        @entries $package:tt $header:literal $own:tt [ $( $peer:tt )* ]
+        $name:literal = { $( $field:tt )* } $(, $( $rest:tt )* )?
+    ) => {
+        $crate::sections_doc_inner!(
+            @entries $package $header $own [ $( $peer )* [peer($name, $( $field )*)] ] $( $( $rest )* )?
+        )
+    };
+
+    (
+        @entries $package:tt $header:literal $own:tt [ $( $peer:tt )* ]
+        $name:literal = $version:literal $(, $( $rest:tt )* )?
+    ) => {
+        $crate::sections_doc_inner!(
+            @entries $package $header $own [ $( $peer )* [peer($name, version = $version)] ] $( $( $rest )* )?
+        )
+    };
+
+    ( @entries $package:tt $header:literal [] [ $( $peer:tt )* ] ) => {
+        concat!(
+            "[",
+            $header,
+            "]",
+            $crate::peers!(inline, "", $header, [ $( $peer )* ]),
+        )
+    };
+
+    ( @entries $package:tt $header:literal [$own:tt []] [ $( [ $( $peer:tt )* ] )* ] ) => {
+        $crate::split_options!(
+            render_table
+            ["", $own, $header]
+            []
+            []
+            $( $( $peer )* ),*
+        )
+    };
+
+    (
+        @entries $package:tt $header:literal [$own:tt [ $( $option:tt )+ ]]
+        [ $( [ $( $peer:tt )* ] )* ]
+    ) => {
+        $crate::split_options!(
+            render_table
+            ["", $own, $header]
+            []
+            []
+            $( $option )+ $( , $( $peer )* )*
+        )
+    };
+
+    ( @entries $package:tt $header:literal $own:tt $peers:tt $( $tt:tt )+ ) => {
+        $crate::core::compile_error!(concat!(
+            "expected `\"name\" = \"version\"` or `\"name\" = { ... }`, found `",
+            stringify!($( $tt )+),
+            "`",
+        ))
+    };
+
+    ( $package:tt, [ $( $tt:tt )* ] $(,)? ) => {
+        $crate::core::compile_error!("`sections_doc` expects `\"header\" = [...]` tables")
+    };
+}
+
+/// Generates the embedded manifest of a single-file cargo script, showing how
+/// to add the current crate as a dependency of the script.
+///
+/// The snippet is the same as the one of [`dep_doc`], in a `cargo` code block
+/// instead of a `TOML` one, so that it can be copied to the frontmatter of a
+/// script.
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::script_dep_doc!(features = ["proc-macro"])]
+/// ```
+#[macro_export]
+macro_rules! script_dep_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::with_package!(script_dep_doc_inner [$($tt)*])
+    };
+}
+
+// This is just a testable version of `script_dep_doc`, in which we can inject
+// a specific crate name and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! script_dep_doc_inner {
+    ( [$name:expr, $version:expr $(, $components:tt)?], [ $( $tt:tt )* ] $(,)? ) => {
+        $crate::section_inner!(
+            [$name, $version $(, $components)?],
+            "dependencies",
+            [@lang "cargo", $($tt)*]
+        )
+    };
+}
+
+/// Generates a `Cargo.toml` code snippet showing how to add the current crate
+/// as a target-specific dependency.
+///
+/// The first argument is the `cfg(...)` predicate the dependency is restricted
+/// to. It is usually passed as regular tokens rather than as a string, so that
+/// it is checked at compile time: a malformed predicate fails the documentation
+/// build instead of being rendered.
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::target_dep_doc!(cfg(windows))]
+/// ```
+///
+/// The predicate can be followed by the usual customization tokens:
+///
+/// ```rust
+/// #![doc = dep_doc::target_dep_doc!(
+///     cfg(all(unix, target_arch = "x86_64")),
+///     features = ["no_std"],
+/// )]
+/// ```
+///
+/// Malformed predicates are rejected:
+///
+/// ```rust,compile_fail
+/// #![doc = dep_doc::target_dep_doc!(cfg(all unix))]
+/// ```
+///
+/// The predicate can also be written as a string, with `cfg = "..."`. The
+/// string is only checked with the `proc-macro` feature:
+///
+/// ```rust
+/// #![doc = dep_doc::target_dep_doc!(cfg = "cfg(windows)", features = ["no_std"])]
+/// ```
+///
+#[cfg_attr(feature = "proc-macro", doc = "```rust,compile_fail")]
+#[cfg_attr(not(feature = "proc-macro"), doc = "```rust,ignore")]
+/// #![doc = dep_doc::target_dep_doc!(cfg = "cfg(all unix)")]
+/// ```
+#[macro_export]
+macro_rules! target_dep_doc {
+    ( cfg = $predicate:literal $(, $( $tt:tt )* )? ) => {
+        $crate::with_package!(target_dep_doc_inner { cfg = $predicate, } [ $( $( $tt )* )? ])
+    };
+
+    ( cfg $predicate:tt $(, $( $tt:tt )* )? ) => {
+        $crate::with_package!(target_dep_doc_inner { cfg $predicate, } [ $( $( $tt )* )? ])
+    };
+}
+
+// This is just a testable version of `target_dep_doc`, in which we can inject
+// a specific crate name and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! target_dep_doc_inner {
+    ( [$name:expr, $version:expr $(, $components:tt)?], cfg = $predicate:literal, [ $( $tt:tt )* ] $(,)? ) => {
+        concat!(
+            $crate::check_cfg_string!($predicate),
+            $crate::section_inner!(
+                [$name, $version $(, $components)?],
+                concat!("target.'", $predicate, "'.dependencies"),
+                [ $( $tt )* ],
+            ),
+        )
+    };
+
+    (
+        [$name:expr, $version:expr $(, $components:tt)?],
+        cfg ( $( $predicate:tt )* ),
+        [ $( $tt:tt )* ] $(,)?
+    ) => {
+        concat!(
+            $crate::check_cfg_predicate!( $( $predicate )* ),
+            $crate::section_inner!(
+                [$name, $version $(, $components)?],
+                $crate::target_header!(stringify!( $( $predicate )* ), "dependencies"),
+                [ $( $tt )* ],
+            ),
+        )
+    };
+}
+
+/// Generates a `Cargo.toml` code snippet showing how to add the current crate
+/// in an arbitrary dependency table.
+///
+/// The first argument is the header of the table, without the brackets. It can
+/// be followed by the usual customization tokens.
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc_section!("target.'cfg(unix)'.dev-dependencies")]
+/// ```
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc_section!("workspace.dependencies", features = ["no_std"])]
+/// ```
+#[macro_export]
+macro_rules! dep_doc_section {
+    ( $header:literal $(, $( $tt:tt )* )? ) => {
+        $crate::with_package!(dep_doc_section_inner { $header, } [$( $( $tt )* )?])
+    };
+}
+
+// This is just a testable version of `dep_doc_section`, in which we can
+// inject a specific crate name and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! dep_doc_section_inner {
+    (
+        [$name:expr, $version:expr $(, $components:tt)?],
+        $header:literal,
+        [ $( $tt:tt )* ] $(,)?
+    ) => {
+        $crate::section_inner!([$name, $version $(, $components)?], $header, [ $($tt)* ])
+    };
+}
+
+/// Generates the same snippet as [`dep_doc`], as a `&'static [u8]`.
+///
+/// The expansion is a constant expression, which makes it usable in `const`
+/// and `static` items, in environments where no allocator is available.
+///
+/// # Example
+///
+/// ```rust
+/// const SNIPPET: &[u8] = dep_doc::dep_doc_bytes!(features = ["no_std"]);
+/// ```
+#[macro_export]
+macro_rules! dep_doc_bytes {
+    ( $( $tt:tt )* ) => {
+        $crate::dep_doc!( $( $tt )* ).as_bytes()
+    };
+}
+
+/// Includes a snippet rendered by the build script with [`Snippet::write`].
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #![doc = dep_doc::include_snippet!("dev")]
+/// ```
+#[macro_export]
+macro_rules! include_snippet {
+    ( $name:literal $(,)? ) => {
+        $crate::core::include_str!(concat!($crate::core::env!("OUT_DIR"), "/", $name, ".md"))
+    };
+}
+
+/// Checks that a file of the crate, usually its README, contains the snippet
+/// [`dep_doc`] generates, and panics otherwise.
+///
+/// This catches READMEs which still show a previous version or name of the
+/// crate. The path is relative to the directory containing the `Cargo.toml`,
+/// and the other arguments are passed to [`dep_doc`].
+///
+/// # Example
+///
+/// It is meant to be called from a test:
+///
+/// ```rust
+/// dep_doc::assert_readme_in_sync!("README.md");
+/// ```
+#[macro_export]
+macro_rules! assert_readme_in_sync {
+    ( $path:literal $(, $( $tt:tt )* )? ) => {
+        $crate::readme_in_sync(
+            $crate::core::include_str!(concat!(
+                $crate::core::env!("CARGO_MANIFEST_DIR"),
+                "/",
+                $path,
+            )),
+            $path,
+            $crate::dep_doc!($( $( $tt )* )?),
+        )
+    };
+}
+
+// Implementation of `assert_readme_in_sync`, which panics with both the
+// expected snippet and the first TOML code block of the file.
+#[doc(hidden)]
+#[track_caller]
+pub fn readme_in_sync(content: &str, path: &str, snippet: &str) {
+    let content = content.replace("\r\n", "\n");
+    if content.contains(snippet) {
+        return;
+    }
+
+    let found = content
+        .match_indices("```")
+        .map(|(idx, _)| &content[idx..])
+        .find(|block| block[3..].to_ascii_lowercase().starts_with("toml"))
+        .map(|block| match block[3..].find("\n```") {
+            Some(end) => &block[..end + 7],
+            None => block,
+        });
+
+    match found {
+        Some(found) => panic!(
+            "`{}` is out of sync, expected:\n\n{}\n\nfound:\n\n{}",
+            path, snippet, found
+        ),
+        None => panic!(
+            "`{}` is out of sync, expected:\n\n{}\n\nbut it has no TOML code block",
+            path, snippet
+        ),
+    }
+}
+
+/// Makes the features the crate is compiled with available to the
+/// `enabled_features` option of [`dep_doc`].
+///
+/// Cargo only tells build scripts which features are enabled. This function is
+/// meant to be called from the build script of the crate, and forwards them to
+/// the compilation of the crate.
+///
+/// # Example
+///
+/// In `build.rs`:
+///
+/// ```rust,no_run
+/// # fn main() {
+/// dep_doc::export_features();
+/// # }
+/// ```
+pub fn export_features() {
+    let mut features = std::env::vars()
+        .filter_map(|(key, _)| key.strip_prefix("CARGO_FEATURE_").map(str::to_string))
+        .collect::<Vec<_>>();
+    features.sort();
+
+    println!("cargo:rustc-env=DEP_DOC_FEATURES={}", features.join(","));
+}
+
+/// Checks that the snippet [`dep_doc`] generates is valid TOML, and panics
+/// otherwise.
+///
+/// The arguments are passed to [`dep_doc`]. This catches the values which
+/// can't be rendered as TOML, such as raw strings without the `proc-macro`
+/// feature, before the documentation is published.
+///
+/// # Example
+///
+/// It is meant to be called from a test:
+///
+/// ```rust
+/// dep_doc::validate_dep_doc!(features = ["proc-macro"], comment = "Exact TOML");
+/// ```
+#[macro_export]
+macro_rules! validate_dep_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::valid_snippet($crate::dep_doc!($( $tt )*))
+    };
+}
+
+// Implementation of `validate_dep_doc`, which panics with the error and the
+// snippet.
+#[doc(hidden)]
+#[track_caller]
+pub fn valid_snippet(snippet: &str) {
+    if let Err(message) = validate::validate_snippet(snippet) {
+        panic!("the snippet is not valid TOML: {}\n\n{}", message, snippet);
+    }
+}
+
+/// Checks that the version of the crate matches the latest git tag, and
+/// panics otherwise.
+///
+/// The tag is found with `git describe --tags --abbrev=0`, run in the
+/// directory containing the `Cargo.toml`. This makes sure that the version the
+/// snippets advertise is the one which is actually tagged. By default, tags are
+/// expected to be the version prefixed with `v`, which can be changed with
+/// `tag_prefix`. Only the tags starting with the prefix are considered, which
+/// allows several crates of a workspace to be tagged independently.
+///
+/// # Example
+///
+/// It is meant to be called from a test, which may be ignored by default and
+/// run before each release:
+///
+/// ```rust,no_run
+/// dep_doc::assert_version_matches_tag!(tag_prefix = "dep_doc-v");
+/// ```
+#[macro_export]
+macro_rules! assert_version_matches_tag {
+    ( $( $tt:tt )* ) => {
+        $crate::split_options!(
+            check_version_tag
+            [$crate::core::env!("CARGO_MANIFEST_DIR"), $crate::core::env!("CARGO_PKG_VERSION")]
+            []
+            []
+            $( $tt )*
+        )
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! check_version_tag {
+    ( [$dir:expr, $version:expr] [] ) => {
+        $crate::version_matches_tag($dir, $version, "v")
+    };
+
+    ( [$dir:expr, $version:expr] [ [tag_prefix = $prefix:literal] ] ) => {
+        $crate::version_matches_tag($dir, $version, $prefix)
+    };
+
+    ( $ctx:tt [ $( [ $( $option:tt )* ] )* ] ) => {
+        $crate::core::compile_error!(concat!(
+            "unknown `assert_version_matches_tag` options `",
+            $( stringify!($( $option )*), )*
+            "`, expected `tag_prefix`",
+        ))
+    };
+}
+
+// Implementation of `assert_version_matches_tag`.
+#[doc(hidden)]
+#[track_caller]
+pub fn version_matches_tag(dir: &str, version: &str, prefix: &str) {
+    let output = std::process::Command::new("git")
+        .args(["describe", "--tags", "--abbrev=0", "--match"])
+        .arg(format!("{}*", prefix))
+        .current_dir(dir)
+        .output();
+
+    let tag = match output {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        }
+        Ok(output) => panic!(
+            "no git tag starting with `{}` found: {}",
+            prefix,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(e) => panic!("failed to run `git describe`: {}", e),
+    };
+
+    if let Err(message) = check_tag(tag.trim(), version, prefix) {
+        panic!("{}", message);
+    }
+}
+
+fn check_tag(tag: &str, version: &str, prefix: &str) -> Result<(), String> {
+    match tag.strip_prefix(prefix) {
+        Some(tagged) if tagged == version => Ok(()),
+        _ => Err(format!(
+            "the crate version `{}` does not match the latest git tag `{}`, expected `{}{}`",
+            version, tag, prefix, version
+        )),
+    }
+}
+
+/// Generates the dependency line of [`dep_doc`], without the code block and
+/// the table header, such as `dep_doc = "0.1.1"`.
+///
+/// The expansion is a `&'static str`, which can be reused outside of the
+/// documentation, for instance in CLI help texts or in error messages.
+///
+/// # Example
+///
+/// ```rust
+/// const SPEC: &str = dep_doc::spec!(features = ["proc-macro"]);
+///
+/// assert_eq!(
+///     SPEC,
+///     concat!(
+///         r#"dep_doc = { version = ""#,
+///         env!("CARGO_PKG_VERSION"),
+///         r#"", features = ["proc-macro"] }"#,
+///     ),
+/// );
+/// ```
+#[macro_export]
+macro_rules! spec {
+    ( $( $tt:tt )* ) => {
+        $crate::with_package!(spec_inner [$($tt)*])
+    };
+}
+
+// This is just a testable version of `spec`, in which we can inject a
+// specific crate name and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! spec_inner {
+    ( [$name:expr, $version:expr $(, $components:tt)?], [ $( $tt:tt )* ] $(,)? ) => {
+        $crate::split_options!(render_spec [$name, $version $(, $components)?] [] [] $( $tt )*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! render_spec {
+    ( [$name:expr, $version:expr $(, $components:tt)?] $options:tt ) => {
+        concat!(
+            $crate::verify!(verify_features, $options),
+            $crate::verify!(verify_git, $options),
+            $crate::render_entry!(
+                [
+                    $name,
+                    [
+                        [
+                            @string "version" = $crate::snippet_version!(
+                                [$name, $version $(, $components)?],
+                                $options
+                            )
+                        ]
+                    ],
+                    "",
+                    inline
+                ]
+                []
+                $options
+            ),
+        )
+    };
+}
+
+/// Generates a diff between the dependency line of a previous version of the
+/// current crate and the one of the current version, for migration guides.
+///
+/// The new line is the one generated by [`spec`], to which the tokens
+/// following `from` are passed.
+///
+/// # Example
+///
+/// ```rust
+/// //! # Upgrading from 0.3
+/// //!
+/// #![doc = dep_doc::upgrade_doc!(from = "0.3")]
+/// ```
+///
+/// This renders as:
+///
+/// ```diff
+///  [dependencies]
+/// -dep_doc = "0.3"
+/// +dep_doc = "0.1.1"
+/// ```
+#[macro_export]
+macro_rules! upgrade_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::with_package!(upgrade_doc_inner [$($tt)*])
+    };
+}
+
+// This is just a testable version of `upgrade_doc`, in which we can inject a
+// specific crate name and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! upgrade_doc_inner {
+    (
+        [$name:expr, $version:expr $(, $components:tt)?],
+        [ from = $from:literal $(, $( $tt:tt )* )? ] $(,)?
+    ) => {
+        $crate::split_options!(
+            render_upgrade
+            [[$name, $version $(, $components)?], $from]
+            []
+            []
+            $( $( $tt )* )?
+        )
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! render_upgrade {
+    ( [[$name:expr, $version:expr $(, $components:tt)?], $from:literal] $options:tt ) => {
+        concat!(
+            "```diff\n [dependencies]\n-",
+            $crate::entry_key!($name, $options),
+            " = \"",
+            $from,
+            "\"\n+",
+            $crate::render_spec!([$name, $version $(, $components)?] $options),
+            "\n```",
+        )
+    };
+}
+
+/// Generates the same fenced snippet as [`dep_doc`], for use outside of the
+/// documentation.
+///
+/// # Example
+///
+/// ```rust
+/// fn help() -> String {
+///     format!("Add the following to your `Cargo.toml`:\n{}", dep_doc::toml_block!())
+/// }
+/// ```
+#[macro_export]
+macro_rules! toml_block {
+    ( $( $tt:tt )* ) => {
+        $crate::dep_doc!( $( $tt )* )
+    };
+}
+
+/// Generates two `Cargo.toml` code snippets, showing how to add the current
+/// crate either from crates.io or from its git repository, when the crate
+/// name in the repository differs from the published one.
+///
+/// The git snippet keeps the published name as the dependency key and uses
+/// the `package` key to name the crate within the repository, so that both
+/// alternatives can be used interchangeably in the user code.
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::renamed_git_dep_doc!(
+///     git = "https://github.com/scrabsha/dep-doc",
+///     package = "dep-doc",
+/// )]
+/// ```
+///
+/// Additional tokens, such as `branch` or `rev`, are added to the git snippet:
+///
+/// ```rust
+/// #![doc = dep_doc::renamed_git_dep_doc!(
+///     git = "https://github.com/scrabsha/dep-doc",
+///     package = "dep-doc",
+///     branch = "main",
+/// )]
+/// ```
+#[macro_export]
+macro_rules! renamed_git_dep_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::with_package!(renamed_git_dep_doc_inner [$($tt)*])
+    };
+}
+
+// This is just a testable version of `renamed_git_dep_doc`, in which we can
+// inject a specific crate name and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! renamed_git_dep_doc_inner {
+    (
+        [$name:expr, $version:expr $(, $components:tt)?],
+        [ git = $git:literal, package = $package:literal $(, $( $rest:tt )* )? ] $(,)?
+    ) => {
+        $crate::split_options!(
+            render_renamed_git
+            [[$name, $version], $git, $package]
+            []
+            []
+            $( $( $rest )* )?
+        )
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! render_renamed_git {
+    ( $ctx:tt $options:tt ) => {
+        $crate::render_renamed_git!(@strip $ctx [] $options)
+    };
+
+    // The package is read from these variables by `with_package`.
+    ( @strip $ctx:tt $keys:tt [ [name_env = $env:literal] $( $rest:tt )* ] ) => {
+        $crate::render_renamed_git!(@strip $ctx $keys [ $( $rest )* ])
+    };
+
+    ( @strip $ctx:tt $keys:tt [ [version_env = $env:literal] $( $rest:tt )* ] ) => {
+        $crate::render_renamed_git!(@strip $ctx $keys [ $( $rest )* ])
+    };
+
+    ( @strip $ctx:tt [ $( $keys:tt )* ] [ $key:tt $( $rest:tt )* ] ) => {
+        $crate::render_renamed_git!(@strip $ctx [ $( $keys )* $key ] [ $( $rest )* ])
+    };
+
+    (
+        @strip
+        [[$name:expr, $version:expr], $git:literal, $package:literal]
+        [ $( [ $( $key:tt )* ] )* ]
+        []
+    ) => {
+        concat!(
+            "From crates.io:\n\n",
+            $crate::section_inner!([$name, $version], "dependencies", []),
+            "\n\nFrom the git repository:\n\n```TOML\n[dependencies]\n",
+            $crate::renamed_import!(
+                [$name, $package, $version],
+                [git = $git $(, $( $key )* )*]
+            ),
+            "\n```",
+        )
+    };
+}
+
+/// Generates a `Cargo.toml` code snippet enabling a feature of the current
+/// crate, followed by a Rust code block showing an item gated behind this
+/// feature.
+///
+/// The item is provided as a string literal and is placed right after the
+/// corresponding `#[cfg(feature = "...")]` attribute. The Rust code block is
+/// marked as `ignore`, as it is meant to be copied in the user code.
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::feature_dep_doc!(
+///     feature = "no_std",
+///     item = "pub use dep_doc::dep_doc_bytes;",
+/// )]
+/// ```
+///
+/// Additional tokens are added to the `Cargo.toml` snippet:
+///
+/// ```rust
+/// #![doc = dep_doc::feature_dep_doc!(
+///     feature = "no_std",
+///     item = "pub use dep_doc::dep_doc_bytes;",
+///     git = "https://github.com/scrabsha/dep-doc",
+/// )]
+/// ```
+#[macro_export]
+macro_rules! feature_dep_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::with_package!(feature_dep_doc_inner [$($tt)*])
+    };
+}
+
+// This is just a testable version of `feature_dep_doc`, in which we can inject
+// a specific crate name and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! feature_dep_doc_inner {
+    (
+        [$name:expr, $version:expr $(, $components:tt)?],
+        [ feature = $feature:literal, item = $item:literal $(, $( $rest:tt )* )? ] $(,)?
+    ) => {
+        concat!(
+            $crate::section_inner!(
+                [$name, $version $(, $components)?],
+                "dependencies",
+                [features = [$feature] $(, $( $rest )* )?]
+            ),
+            "\n\n```rust,ignore\n#[cfg(feature = \"",
+            $feature,
+            "\")]\n",
+            $item,
+            "\n```",
+        )
+    };
+}
+
+/// Generates a `Cargo.toml` code snippet enabling exactly the features passed
+/// as first arguments.
+///
+/// This is meant for the documentation of feature-gated modules and items, so
+/// that each of them shows what its users must add to their `Cargo.toml`. The
+/// following tokens are the ones of [`dep_doc`].
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc_for_features!("proc-macro")]
+/// ```
+///
+/// ```rust
+/// #![doc = dep_doc::dep_doc_for_features!(
+///     "proc-macro",
+///     git = "https://github.com/scrabsha/dep-doc",
+/// )]
+/// ```
+#[macro_export]
+macro_rules! dep_doc_for_features {
+    ( $( $tt:tt )* ) => {
+        $crate::with_package!(dep_doc_for_features_inner [$($tt)*])
+    };
+}
+
+// This is just a testable version of `dep_doc_for_features`, in which we can
+// inject a specific crate name and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! dep_doc_for_features_inner {
+    ( $package:tt, [ $( $tt:tt )* ] $(,)? ) => {
+        $crate::dep_doc_for_features_inner!(@features $package [] [ $( $tt )* ])
+    };
+
+    (
+        @features $package:tt
+        [ $( $feature:literal ),* ]
+        [ $next:literal $(, $( $rest:tt )* )? ]
+    ) => {
+        $crate::dep_doc_for_features_inner!(
+            @features $package
+            [ $( $feature, )* $next ]
+            [ $( $( $rest )* )? ]
+        )
+    };
+
+    ( @features $package:tt [] [ $( $rest:tt )* ] ) => {
+        $crate::core::compile_error!("`dep_doc_for_features` expects at least one feature")
+    };
+
+    (
+        @features [$name:expr, $version:expr $(, $components:tt)?]
+        [ $( $feature:literal ),+ ]
+        [ $( $rest:tt )* ]
+    ) => {
+        $crate::section_inner!(
+            [$name, $version $(, $components)?],
+            "dependencies",
+            [features = [ $( $feature ),+ ], $( $rest )*]
+        )
+    };
+}
+
+/// Generates a `Cargo.toml` code snippet showing how to add the current crate
+/// as a target-specific dependency, for several targets at once.
+///
+/// The first argument is the list of `cfg` predicates. Each of them gets its own
+/// `[target.'cfg(...)'.dependencies]` table, in the same code block. The
+/// following tokens are applied to every table.
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::multi_target_dep_doc!(["unix", "windows"], features = ["no_std"])]
+/// ```
+#[macro_export]
+macro_rules! multi_target_dep_doc {
+    ( [ $( $cfg:tt )* ] $(, $( $tt:tt )* )? ) => {
+        $crate::with_package!(multi_target_dep_doc_inner { [ $( $cfg )* ], } [ $( $( $tt )* )? ])
+    };
+}
+
+// This is just a testable version of `multi_target_dep_doc`, in which we can
+// inject a specific crate name and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! multi_target_dep_doc_inner {
+    (
+        [$name:expr, $version:expr $(, $components:tt)?],
+        [ $( $cfg:literal ),+ $(,)? ],
+        [ $( $tt:tt )* ] $(,)?
+    ) => {
+        $crate::sections_inner!(
+            [$name, $version $(, $components)?],
+            [ $( $crate::target_header!($cfg, "dependencies") ),+ ],
+            [ $( $tt )* ],
+        )
+    };
+}
+
+/// Generates a `Cargo.toml` code snippet showing how to add the current crate
+/// as a dependency, preceded by a compatibility matrix.
+///
+/// The matrix is a list of `(left, right)` pairs, each of them being rendered
+/// as a TOML comment. It is typically used to document which version of the
+/// current crate pairs with which version of another crate. With the
+/// `proc-macro` feature, the left column is padded to its widest entry, so
+/// that the arrows are aligned. The following tokens are passed to
+/// [`dep_doc`].
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::compat_matrix_doc!(
+///     [("dep_doc 0.1", "rustc 1.54+"), ("dep_doc 0.2", "rustc 1.56+")],
+///     features = ["no_std"],
+/// )]
+/// ```
+#[macro_export]
+macro_rules! compat_matrix_doc {
+    ( [ $( $pair:tt )* ] $(, $( $tt:tt )* )? ) => {
+        $crate::with_package!(compat_matrix_doc_inner { [ $( $pair )* ], } [ $( $( $tt )* )? ])
+    };
+}
+
+// This is just a testable version of `compat_matrix_doc`, in which we can
+// inject a specific crate name and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! compat_matrix_doc_inner {
+    ( [$name:expr, $version:expr $(, $components:tt)?], [ $( $pair:tt )* ], [ $( $tt:tt )* ] $(,)? ) => {
+        $crate::section_inner!(
+            [$name, $version $(, $components)?],
+            "dependencies",
+            [compat_matrix = [ $( $pair )* ], $( $tt )*]
+        )
+    };
+}
+
+/// Generates a `Cargo.toml` code snippet showing how to add the current crate
+/// as an optional dependency, enabled by a feature of the user crate.
+///
+/// The code block contains the optional dependency, and the `[features]` table
+/// entry which enables it. The feature uses the `dep:` syntax, which is
+/// available since Rust 1.60. Additional tokens are added to the dependency.
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::optional_dep_full_doc!(feature = "docs")]
+/// ```
+#[macro_export]
+macro_rules! optional_dep_full_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::with_package!(optional_dep_full_doc_inner [$($tt)*])
+    };
+}
+
+// This is just a testable version of `optional_dep_full_doc`, in which we can
+// inject a specific crate name and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! optional_dep_full_doc_inner {
+    ( [$name:expr, $version:expr $(, $components:tt)?], [ feature = $feature:literal $(, $( $tt:tt )* )? ] $(,)? ) => {
+        $crate::section_inner!(
+            [$name, $version $(, $components)?],
+            "dependencies",
+            [optional_feature = $feature $(, $( $tt )* )?]
+        )
+    };
+}
+
+/// Generates a `Cargo.toml` code snippet overriding the current crate with its
+/// git repository, in the `[patch.crates-io]` table.
+///
+/// The repository is the `repository` field of the `Cargo.toml` of the crate,
+/// unless a `git` URL is provided. Additional tokens, such as `branch` or
+/// `rev`, are added to the patch.
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::patch_doc!()]
+/// ```
+///
+/// ```rust
+/// #![doc = dep_doc::patch_doc!(branch = "fix-parser")]
+/// ```
+///
+/// As for [`dep_doc`], only one of `branch`, `tag` and `rev` can be
+/// specified:
+///
+/// ```rust,compile_fail
+/// #![doc = dep_doc::patch_doc!(branch = "fix-parser", rev = "5d3117d")]
+/// ```
+#[macro_export]
+macro_rules! patch_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::patch_doc_inner!(
+            [$crate::core::env!("CARGO_PKG_NAME"), $crate::core::env!("CARGO_PKG_REPOSITORY")],
+            [$($tt)*],
+        )
+    };
+}
+
+// This is just a testable version of `patch_doc`, in which we can inject a
+// specific crate name and repository.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! patch_doc_inner {
+    ( $ctx:tt, [ $( $tt:tt )* ] $(,)? ) => {
+        $crate::split_options!(render_patch $ctx [] [] $( $tt )*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! render_patch {
+    ( $ctx:tt $options:tt ) => {
+        $crate::render_patch!(@find $ctx $options $options)
+    };
+
+    // The fields go through `git_keys`, which places the reference right
+    // after the repository and rejects conflicting references, as for the
+    // main entry.
+    ( @find [$name:expr, $repository:expr] [ $( $option:tt )* ] [] ) => {
+        $crate::render_patch!(
+            @render $crate::git_keys!(
+                [$name, [], "", inline]
+                []
+                [git]
+                [ $( $option )* ]
+                [ [@string "git" = $repository] $( $option )* ]
+            )
+        )
+    };
+
+    ( @find [$name:expr, $repository:expr] $options:tt [ [git = $url:tt] $( $rest:tt )* ] ) => {
+        $crate::render_patch!(
+            @render $crate::git_keys!([$name, [], "", inline] [] [] $options $options)
+        )
+    };
+
+    ( @find $ctx:tt $options:tt [ $skip:tt $( $rest:tt )* ] ) => {
+        $crate::render_patch!(@find $ctx $options [ $( $rest )* ])
+    };
+
+    ( @render $entry:expr ) => {
+        concat!("```TOML\n[patch.crates-io]\n", $entry, "\n```")
+    };
+}
+
+/// Generates a `Cargo.toml` code snippet overriding the profile settings of
+/// the current crate, in the `[profile.dev.package.<name>]` table.
+///
+/// This is useful for crates which are too slow without optimizations, and
+/// recommend optimizing them even in debug builds. The keys to set are passed
+/// to the macro, and default to `opt-level = 3`. Another profile can be
+/// selected with `profile = "..."`.
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::profile_doc!()]
+/// ```
+///
+/// ```rust
+/// #![doc = dep_doc::profile_doc!(profile = "test", opt-level = 2, debug-assertions = false)]
+/// ```
+#[macro_export]
+macro_rules! profile_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::profile_doc_inner!([$crate::core::env!("CARGO_PKG_NAME")], [$($tt)*])
+    };
+}
+
+// This is just a testable version of `profile_doc`, in which we can inject a
+// specific crate name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! profile_doc_inner {
+    ( $ctx:tt, [ $( $tt:tt )* ] $(,)? ) => {
+        $crate::split_options!(render_profile $ctx [] [] $( $tt )*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! render_profile {
+    ( $ctx:tt $options:tt ) => {
+        $crate::render_profile!(@find $ctx ["dev"] [] $options)
+    };
+
+    ( @find $ctx:tt $profile:tt $keys:tt [ [profile = $name:literal] $( $rest:tt )* ] ) => {
+        $crate::render_profile!(@find $ctx [$name] $keys [ $( $rest )* ])
+    };
+
+    ( @find $ctx:tt $profile:tt [ $( $key:tt )* ] [ $next:tt $( $rest:tt )* ] ) => {
+        $crate::render_profile!(@find $ctx $profile [ $( $key )* $next ] [ $( $rest )* ])
+    };
+
+    ( @find $ctx:tt $profile:tt [] [] ) => {
+        $crate::render_profile!(@render $ctx $profile [ [opt-level = 3] ])
+    };
+
+    ( @find $ctx:tt $profile:tt $keys:tt [] ) => {
+        $crate::render_profile!(@render $ctx $profile $keys)
+    };
+
+    ( @render [$name:expr] [$profile:expr] [ $( $key:tt )+ ] ) => {
+        concat!(
+            "```TOML\n[profile.",
+            $profile,
+            ".package.",
+            $name,
+            "]",
+            $( "\n", $crate::render_key! $key, )+
+            "\n```",
+        )
+    };
+}
+
+/// Generates two `Cargo.toml` code snippets: one adding the current crate to
+/// the `[workspace.dependencies]` table, and one inheriting it in a member
+/// crate.
+///
+/// The tokens passed to the macro are added to the workspace dependency. See
+/// [`workspace_pattern_doc`] for a variant which labels each code block.
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::workspace_dep_doc!(features = ["no_std"])]
+/// ```
+#[macro_export]
+macro_rules! workspace_dep_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::with_package!(workspace_dep_doc_inner [$($tt)*])
+    };
+}
+
+// This is just a testable version of `workspace_dep_doc`, in which we can
+// inject a specific crate name and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! workspace_dep_doc_inner {
+    ( [$name:expr, $version:expr $(, $components:tt)?], [ $( $tt:tt )* ] $(,)? ) => {
+        concat!(
+            $crate::section_inner!([$name, $version $(, $components)?], "workspace.dependencies", [ $( $tt )* ]),
+            "\n\n```TOML\n[dependencies]\n",
+            $crate::workspace_import!(@dotted $name),
+            "\n```",
+        )
+    };
+}
+
+/// Generates the `Cargo.toml` code snippets showing how to add the current
+/// crate as a workspace dependency, and how to inherit it from a member crate.
+///
+/// The first code block is meant for the workspace root manifest, and the
+/// second one for each member manifest. Each of them is preceded by a short
+/// label. The tokens passed to the macro are added to the workspace
+/// dependency. See [`workspace_dep_doc`] for an unlabelled variant.
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::workspace_pattern_doc!()]
+/// ```
+#[macro_export]
+macro_rules! workspace_pattern_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::with_package!(workspace_pattern_doc_inner [$($tt)*])
+    };
+}
+
+// This is just a testable version of `workspace_pattern_doc`, in which we can
+// inject a specific crate name and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! workspace_pattern_doc_inner {
+    ( [$name:expr, $version:expr $(, $components:tt)?], [ $( $tt:tt )* ] $(,)? ) => {
+        concat!(
+            "In the workspace `Cargo.toml`:\n\n",
+            $crate::section_inner!([$name, $version $(, $components)?], "workspace.dependencies", [ $( $tt )* ]),
+            "\n\nIn the `Cargo.toml` of each member crate:\n\n```TOML\n[dependencies]\n",
+            $crate::workspace_import!($name),
+            "\n```",
+        )
+    };
+}
+
+/// Generates a shell snippet showing how to add the current crate as a
+/// dependency with `cargo add`.
+///
+/// It accepts the same tokens as [`dep_doc`], and translates them into the
+/// equivalent `cargo add` flags. The following keys are supported: `features`,
+/// `default-features`, `no_default_features`, `no_std`, `optional`, `git`,
+/// `branch`, `tag`, `rev`, `path`, `registry` and `as`, which renames the
+/// dependency. Other keys result in a compilation error.
+///
+/// The version is passed as `name@version`, and follows the `style`,
+/// `precision` and `prerelease` options as the `version` key of [`dep_doc`]
+/// does.
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::cargo_add_doc!(
+///     features = ["no_std"],
+///     git = "https://github.com/scrabsha/dep-doc",
+/// )]
+/// ```
+#[macro_export]
+macro_rules! cargo_add_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::with_package!(cargo_add_doc_inner [$($tt)*])
+    };
+}
+
+// This is just a testable version of `cargo_add_doc`, in which we can inject a
+// specific crate name and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! cargo_add_doc_inner {
+    ( [$name:expr, $version:expr $(, $components:tt)?], [ $( $tt:tt )* ] $(,)? ) => {
+        $crate::split_options!(
+            render_cargo_add [$name, $version $(, $components)?] [] [] $( $tt )*
+        )
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! render_cargo_add {
+    ( [$name:expr, $version:expr $(, $components:tt)?] [ $( $option:tt )* ] ) => {
+        concat!(
+            "```console\n$ cargo add ",
+            $name,
+            "@",
+            $crate::snippet_version!([$name, $version $(, $components)?], [ $( $option )* ]),
+            $( $crate::cargo_add_flag! $option, )*
+            "\n```",
+        )
+    };
+}
+
+// Translates a `key = value` pair into the corresponding `cargo add` flag.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! cargo_add_flag {
+    ( features = [ $( $feature:literal ),* $(,)? ] ) => {
+        $crate::cargo_add_flag!(@features [] $( $feature )*)
+    };
+
+    ( @features [] ) => {
+        ""
+    };
+
+    ( @features [] $first:literal $( $feature:literal )* ) => {
+        concat!(" --features ", $first, $( ",", $feature, )*)
+    };
+
+    ( default-features = false ) => {
+        " --no-default-features"
+    };
+
+    ( default-features = true ) => {
+        ""
+    };
+
+    ( no_default_features ) => {
+        " --no-default-features"
+    };
+
+    ( no_std $( = $features:tt )? ) => {
+        " --no-default-features"
+    };
+
+    ( optional ) => {
+        " --optional"
+    };
+
+    ( optional = true ) => {
+        " --optional"
+    };
+
+    ( optional = false ) => {
+        ""
+    };
+
+    ( git = $value:literal ) => {
+        concat!(" --git ", $value)
+    };
+
+    ( branch = $value:literal ) => {
+        concat!(" --branch ", $value)
+    };
+
+    ( tag = $value:literal ) => {
+        concat!(" --tag ", $value)
+    };
+
+    ( rev = $value:literal ) => {
+        concat!(" --rev ", $value)
+    };
+
+    ( path = $value:literal ) => {
+        concat!(" --path ", $value)
+    };
+
+    ( registry = $value:literal ) => {
+        concat!(" --registry ", $value)
+    };
+
+    ( as = $value:literal ) => {
+        concat!(" --rename ", $value)
+    };
+
+    // The version requirement options are rendered in the `name@version`
+    // argument.
+    ( style = $value:tt ) => {
+        ""
+    };
+
+    ( precision = $value:tt ) => {
+        ""
+    };
+
+    ( prerelease = $value:tt ) => {
+        ""
+    };
+
+    // The package is read from these variables by `with_package`.
+    ( name_env = $env:literal ) => {
+        ""
+    };
+
+    ( version_env = $env:literal ) => {
+        ""
+    };
+
+    ( $( $tt:tt )* ) => {
+        $crate::core::compile_error!(concat!(
+            "`",
+            stringify!( $( $tt )* ),
+            "` has no `cargo add` equivalent",
+        ))
+    };
+}
+
+/// Generates the two ways of adding the current crate as a dependency: a
+/// `cargo add` invocation, followed by the equivalent `Cargo.toml` snippet.
+///
+/// Both are generated from the same tokens, which are passed to
+/// [`cargo_add_doc`] and to [`dep_doc`]. Only the keys supported by
+/// [`cargo_add_doc`] can be used.
+///
+/// # Example
+///
+/// ```rust
+/// //! # Installation
+/// //!
+/// #![doc = dep_doc::installation_doc!(features = ["proc-macro"])]
+/// ```
+#[macro_export]
+macro_rules! installation_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::with_package!(installation_doc_inner [$($tt)*])
+    };
+}
+
+// This is just a testable version of `installation_doc`, in which we can
+// inject a specific crate name and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! installation_doc_inner {
+    ( [$name:expr, $version:expr $(, $components:tt)?], [ $( $tt:tt )* ] $(,)? ) => {
+        concat!(
+            $crate::cargo_add_doc_inner!([$name, $version $(, $components)?], [ $( $tt )* ]),
+            "\n\nOr add the following to your `Cargo.toml`:\n\n",
+            $crate::dep_doc_inner!([$name, $version $(, $components)?], [ $( $tt )* ]),
+        )
+    };
+}
+
+/// Generates a shell snippet showing how to install the binaries of the
+/// current crate with `cargo install`.
+///
+/// Passing `binstall` adds the equivalent [`cargo binstall`][binstall]
+/// invocation, which downloads prebuilt binaries instead of compiling them.
+///
+/// [binstall]: https://github.com/cargo-bins/cargo-binstall
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::cargo_install_doc!()]
+/// ```
+///
+/// ```rust
+/// #![doc = dep_doc::cargo_install_doc!(binstall)]
+/// ```
+///
+/// Crates published to an alternative registry can pass `registry = "..."`,
+/// which is forwarded to `cargo binstall` as well:
+///
+/// ```rust
+/// #![doc = dep_doc::cargo_install_doc!(registry = "my-registry")]
+/// ```
+///
+/// ```rust
+/// #![doc = dep_doc::cargo_install_doc!(binstall, registry = "my-registry")]
+/// ```
+#[macro_export]
+macro_rules! cargo_install_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::cargo_install_doc_inner!(
+            [$crate::core::env!("CARGO_PKG_NAME"), $crate::core::env!("CARGO_PKG_VERSION")],
+            [$($tt)*],
+        )
+    };
+}
+
+// This is just a testable version of `cargo_install_doc`, in which we can
+// inject a specific crate name and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! cargo_install_doc_inner {
+    ( [$name:expr, $version:expr], [] $(,)? ) => {
+        concat!(
+            "```console\n$ cargo install ",
+            $name,
+            " --version ",
+            $version,
+            "\n```",
+        )
+    };
+
+    ( [$name:expr, $version:expr], [ binstall $(,)? ] $(,)? ) => {
+        concat!(
+            "```console\n$ cargo install ",
+            $name,
+            " --version ",
+            $version,
+            "\n$ cargo binstall ",
+            $name,
+            "\n```",
+        )
+    };
+
+    ( [$name:expr, $version:expr], [ registry = $registry:literal $(,)? ] $(,)? ) => {
+        concat!(
+            "```console\n$ cargo install ",
+            $name,
+            " --version ",
+            $version,
+            " --registry ",
+            $registry,
+            "\n```",
+        )
+    };
+
+    (
+        [$name:expr, $version:expr],
+        [ binstall, registry = $registry:literal $(,)? ] $(,)?
+    ) => {
+        concat!(
+            "```console\n$ cargo install ",
+            $name,
+            " --version ",
+            $version,
+            " --registry ",
+            $registry,
+            "\n$ cargo binstall ",
+            $name,
+            " --registry ",
+            $registry,
+            "\n```",
+        )
+    };
+
+    (
+        [$name:expr, $version:expr],
+        [ registry = $registry:literal, binstall $(,)? ] $(,)?
+    ) => {
+        $crate::cargo_install_doc_inner!([$name, $version], [binstall, registry = $registry])
+    };
+
+    ( [$name:expr, $version:expr], [ $( $tt:tt )* ] $(,)? ) => {
+        $crate::core::compile_error!(concat!(
+            "unexpected `",
+            stringify!($( $tt )*),
+            "`, expected `binstall`, `registry = \"...\"` or both",
+        ))
+    };
+}
+
+/// Generates a `MODULE.bazel` snippet showing how to add the current crate as
+/// a dependency with the `crate_universe` extension of [`rules_rust`].
+///
+/// The following keys are supported: `features`, `default-features` and
+/// `no_default_features`. Other keys result in a compilation error. The crate
+/// is then available as `@crates//:<name>`.
+///
+/// [`rules_rust`]: https://github.com/bazelbuild/rules_rust
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::bazel_doc!(features = ["proc-macro"])]
+/// ```
+#[macro_export]
+macro_rules! bazel_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::with_package!(bazel_doc_inner [$($tt)*])
+    };
+}
+
+// This is just a testable version of `bazel_doc`, in which we can inject a
+// specific crate name and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! bazel_doc_inner {
+    ( [$name:expr, $version:expr $(, $components:tt)?], [ $( $tt:tt )* ] $(,)? ) => {
+        $crate::split_options!(render_bazel [$name, $version $(, $components)?] [] [] $( $tt )*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! render_bazel {
+    ( [$name:expr, $version:expr $(, $components:tt)?] [ $( $option:tt )* ] ) => {
+        concat!(
+            "```starlark\n",
+            "crate = use_extension(\"@rules_rust//crate_universe:extensions.bzl\", \"crate\")\n",
+            "crate.spec(\n    package = \"",
+            $name,
+            "\",\n    version = \"",
+            $version,
+            "\",\n",
+            $( $crate::bazel_attr! $option, )*
+            ")\ncrate.from_specs()\nuse_repo(crate, \"crates\")\n```",
+        )
+    };
+}
+
+// Translates a `key = value` pair into the corresponding `crate.spec`
+// attribute.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! bazel_attr {
+    ( features = [ $( $feature:literal ),* $(,)? ] ) => {
+        $crate::bazel_attr!(@features [] $( $feature )*)
+    };
+
+    ( @features [] ) => {
+        ""
+    };
+
+    ( @features [] $first:literal $( $feature:literal )* ) => {
+        concat!("    features = [\"", $first, "\"", $( ", \"", $feature, "\"", )* "],\n")
+    };
+
+    ( default-features = false ) => {
+        "    default_features = False,\n"
+    };
+
+    ( default-features = true ) => {
+        ""
+    };
+
+    ( no_default_features ) => {
+        "    default_features = False,\n"
+    };
+
+    // The package is read from these variables by `with_package`.
+    ( name_env = $env:literal ) => {
+        ""
+    };
+
+    ( version_env = $env:literal ) => {
+        ""
+    };
+
+    ( $( $tt:tt )* ) => {
+        $crate::core::compile_error!(concat!(
+            "`",
+            stringify!( $( $tt )* ),
+            "` has no `crate.spec` equivalent",
+        ))
+    };
+}
+
+/// Generates the instructions adding the current crate to a [Buck2] project
+/// with [Reindeer], which generates the Buck targets of the third-party crates
+/// listed in `third-party/Cargo.toml`.
+///
+/// It accepts the same tokens as [`dep_doc`]. The crate is then available as
+/// `//third-party:<name>`.
+///
+/// [Buck2]: https://buck2.build
+/// [Reindeer]: https://github.com/facebookincubator/reindeer
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::buck_doc!(features = ["proc-macro"])]
+/// ```
+#[macro_export]
+macro_rules! buck_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::with_package!(buck_doc_inner [$($tt)*])
+    };
+}
+
+// This is just a testable version of `buck_doc`, in which we can inject a
+// specific crate name and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! buck_doc_inner {
+    ( [$name:expr, $version:expr $(, $components:tt)?], [ $( $tt:tt )* ] $(,)? ) => {
+        concat!(
+            "Add the following to `third-party/Cargo.toml`:\n\n",
+            $crate::dep_doc_inner!([$name, $version $(, $components)?], [ $( $tt )* ]),
+            "\n\nThen run `reindeer buckify` and depend on `//third-party:",
+            $name,
+            "`.",
+        )
+    };
+}
+
+/// Generates a Markdown table of the features of the current crate, read from
+/// its `Cargo.toml`.
+///
+/// Each feature of the `[features]` table gets a row stating whether it is
+/// enabled by default, directly or through another feature. The `##` comments
+/// placed right before a feature are used as its description:
+///
+/// ```toml
+/// [features]
+/// default = ["std"]
+/// ## Uses the standard library.
+/// std = []
+/// ```
+///
+/// This macro requires the `proc-macro` feature.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// //! # Features
+/// //!
+/// #![doc = dep_doc::feature_doc!()]
+/// ```
+#[macro_export]
+macro_rules! feature_doc {
+    () => {
+        $crate::proc_macro_backend!(feature_doc, [])
+    };
+}
+
+/// Fails the build when the version of the current crate is a pre-release,
+/// such as `2.0.0-rc.1`, or has build metadata.
+///
+/// This prevents publishing documentation advertising a release candidate.
+/// The check can be restricted to release builds by naming an environment
+/// variable with `env`: the build then only fails when this variable is set
+/// to a non-empty value. The [`dep_doc`] snippet itself can reject pre-release
+/// versions with `prerelease = deny`.
+///
+/// This macro requires the `proc-macro` feature.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// dep_doc::deny_prerelease!(env = "RELEASE_BUILD");
+/// ```
+#[macro_export]
+macro_rules! deny_prerelease {
+    () => {
+        $crate::proc_macro_backend!(@item deny_prerelease, []);
+    };
+
+    ( env = $env:literal $(,)? ) => {
+        $crate::proc_macro_backend!(@item deny_prerelease, [$env]);
+    };
+}
+
+/// Generates a Markdown table of the crates of the workspace the current crate
+/// belongs to, with their current version.
+///
+/// The members are read from the `[workspace]` table of the workspace
+/// manifest. Members which are not published, because of `publish = false`,
+/// are left out. This is meant for the overview of an umbrella crate.
+///
+/// This macro requires the `proc-macro` feature.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// //! # Ecosystem
+/// //!
+/// #![doc = dep_doc::workspace_members_doc!()]
+/// ```
+#[macro_export]
+macro_rules! workspace_members_doc {
+    () => {
+        $crate::proc_macro_backend!(workspace_members_doc, [])
+    };
+}
+
+/// Generates a Markdown table of the crates the current crate integrates with.
+///
+/// Each optional dependency of the `Cargo.toml` of the crate is listed with the
+/// features enabling it, the crate it refers to and its version requirement.
+/// Dependencies without a version, such as path dependencies, are listed with
+/// `*`. Keeping the table generated means it can't drift from the manifest when
+/// an integration is bumped.
+///
+/// This macro requires the `proc-macro` feature.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// //! # Integrations
+/// //!
+/// #![doc = dep_doc::integrations_doc!()]
+/// ```
+#[macro_export]
+macro_rules! integrations_doc {
+    () => {
+        $crate::proc_macro_backend!(integrations_doc, [])
+    };
+}
+
+/// Generates the command running an example of the current crate, with the
+/// features it requires.
+///
+/// The example is looked up in the `Cargo.toml` of the crate. When it has
+/// `required-features`, they are passed to `cargo run`, and its `[[example]]`
+/// table is rendered as well. Examples which don't exist are rejected, so that
+/// the documentation can't refer to a renamed or deleted example.
+///
+/// This macro requires the `proc-macro` feature.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #![doc = dep_doc::example_doc!("server")]
+/// ```
+#[macro_export]
+macro_rules! example_doc {
+    ( $name:literal $(,)? ) => {
+        $crate::proc_macro_backend!(example_doc, [$name])
+    };
+}
+
+/// Generates a "recommended setup" section: a named [`dep_doc`] snippet
+/// enabling a set of features, followed by a table of what each of them
+/// unlocks.
+///
+/// The descriptions are the `##` comments of the features in the `Cargo.toml`
+/// of the crate, as for [`feature_doc`]. Features which are not declared
+/// there are rejected. The tokens following the features are passed to
+/// [`dep_doc`].
+///
+/// This macro requires the `proc-macro` feature.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #![doc = dep_doc::feature_scenario_doc!("Async runtime", features = ["tokio", "rt"])]
+/// ```
+#[macro_export]
+macro_rules! feature_scenario_doc {
+    (
+        $scenario:literal,
+        features = [ $( $feature:literal ),+ $(,)? ]
+        $(, $( $tt:tt )* )?
+    ) => {
+        concat!(
+            "**",
+            $scenario,
+            "**\n\n",
+            $crate::dep_doc!(features = [ $( $feature ),+ ] $(, $( $tt )* )?),
+            "\n\n",
+            $crate::proc_macro_backend!(feature_descriptions, [ $( $feature ),+ ]),
+        )
+    };
+}
+
+/// Selects between two snippets, depending on whether the documentation is built
+/// by docs.rs.
+///
+/// This is useful to show the published version on docs.rs, and the git
+/// repository in documentation built locally, which may describe unreleased
+/// changes. docs.rs is detected with the `DOCS_RS` environment variable it
+/// sets.
+///
+/// This macro requires the `proc-macro` feature.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #![doc = dep_doc::docsrs_select!(
+///     docsrs => dep_doc::dep_doc!(),
+///     local => dep_doc::dep_doc!(git = "https://github.com/scrabsha/dep-doc"),
+/// )]
+/// ```
+///
+/// Without the `proc-macro` feature, the same result can be achieved with the
+/// `docsrs` configuration option, which docs.rs passes to `rustdoc`:
+///
+/// ```rust
+/// #![cfg_attr(docsrs, doc = dep_doc::dep_doc!())]
+/// #![cfg_attr(not(docsrs), doc = dep_doc::dep_doc!(git = "https://github.com/scrabsha/dep-doc"))]
+/// ```
+#[macro_export]
+macro_rules! docsrs_select {
+    ( $( $tt:tt )* ) => {
+        $crate::proc_macro_backend!(docsrs_select, [$($tt)*])
+    };
+}
+
+/// Generates a sentence stating the minimum supported Rust version of the
+/// current crate, as declared by the `rust-version` field of its `Cargo.toml`.
+///
+/// The version is read from the `CARGO_PKG_RUST_VERSION` environment variable,
+/// which is set by Cargo 1.64 and later. With the `proc-macro` feature, the
+/// manifest is read when this variable is not available, which is why the
+/// examples below are only run with this feature.
+///
+/// # Example
+///
+#[cfg_attr(feature = "proc-macro", doc = "```rust")]
+#[cfg_attr(not(feature = "proc-macro"), doc = "```rust,ignore")]
+/// #![doc = dep_doc::msrv_doc!()]
+/// ```
+///
+/// Passing `badge` generates a shields.io badge instead:
+///
+#[cfg_attr(feature = "proc-macro", doc = "```rust")]
+#[cfg_attr(not(feature = "proc-macro"), doc = "```rust,ignore")]
+/// #![doc = dep_doc::msrv_doc!(badge)]
+/// ```
+#[macro_export]
+macro_rules! msrv_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::msrv_doc_inner!([$crate::rust_version!()], [$($tt)*])
+    };
+}
+
+// This is just a testable version of `msrv_doc`, in which we can inject a
+// specific Rust version.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! msrv_doc_inner {
+    ( [$msrv:expr], [] $(,)? ) => {
+        concat!("The minimum supported Rust version (MSRV) is ", $msrv, ".")
+    };
+
+    ( [$msrv:expr], [ badge $(,)? ] $(,)? ) => {
+        $crate::msrv_badge!($msrv)
+    };
+
+    ( [$msrv:expr], [ $( $tt:tt )* ] $(,)? ) => {
+        $crate::core::compile_error!(concat!(
+            "unknown `msrv_doc` option `",
+            stringify!($( $tt )*),
+            "`, expected `badge`",
+        ))
+    };
+}
+
+/// Generates a row of badges for the current crate: its crates.io page, its
+/// documentation on docs.rs and its license.
+///
+/// The badges link to the pages of the version being documented.
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::badges_doc!()]
+/// ```
+///
+/// Passing `msrv` adds a badge stating the minimum supported Rust version, as
+/// [`msrv_doc`] does:
+///
+#[cfg_attr(feature = "proc-macro", doc = "```rust")]
+#[cfg_attr(not(feature = "proc-macro"), doc = "```rust,ignore")]
+/// #![doc = dep_doc::badges_doc!(msrv)]
+/// ```
+///
+/// The crates.io, docs.rs and license badges assume that the crate is published
+/// on crates.io. Passing `registry = "..."` removes them, leaving only the
+/// MSRV badge, if requested.
+#[macro_export]
+macro_rules! badges_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::badges_doc_inner!(
+            [
+                $crate::core::env!("CARGO_PKG_NAME"),
+                $crate::core::env!("CARGO_PKG_VERSION"),
+                $crate::core::env!("CARGO_PKG_LICENSE"),
+            ],
+            [$($tt)*],
+        )
+    };
+}
+
+// This is just a testable version of `badges_doc`, in which we can inject a
+// specific crate name, version, license and Rust version.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! badges_doc_inner {
+    ( $ctx:tt, [ $( $tt:tt )* ] $(,)? ) => {
+        $crate::split_options!(render_badges $ctx [] [] $( $tt )*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! render_badges {
+    ( $ctx:tt $options:tt ) => {
+        $crate::render_badges!(@find $ctx [] [] $options)
+    };
+
+    ( @find $ctx:tt $msrv:tt $registry:tt [ [msrv] $( $rest:tt )* ] ) => {
+        $crate::render_badges!(@find $ctx [$crate::rust_version!()] $registry [ $( $rest )* ])
+    };
+
+    ( @find $ctx:tt $msrv:tt $registry:tt [ [msrv = $new:expr] $( $rest:tt )* ] ) => {
+        $crate::render_badges!(@find $ctx [$new] $registry [ $( $rest )* ])
+    };
+
+    ( @find $ctx:tt $msrv:tt $registry:tt [ [registry = $new:literal] $( $rest:tt )* ] ) => {
+        $crate::render_badges!(@find $ctx $msrv [$new] [ $( $rest )* ])
+    };
+
+    ( @find $ctx:tt $msrv:tt $registry:tt [ [ $( $option:tt )* ] $( $rest:tt )* ] ) => {
+        $crate::core::compile_error!(concat!(
+            "unknown `badges_doc` option `",
+            stringify!($( $option )*),
+            "`, expected `msrv` or `registry`",
+        ))
+    };
+
+    // The crates.io, docs.rs and license badges are fetched from crates.io.
+    ( @find $ctx:tt [] [$registry:literal] [] ) => {
+        ""
+    };
+
+    ( @find $ctx:tt [$msrv:expr] [$registry:literal] [] ) => {
+        $crate::msrv_badge!($msrv)
+    };
+
+    ( @find [$name:expr, $version:expr, $license:expr $(,)?] [ $( $msrv:expr )? ] [] [] ) => {
+        concat!(
+            "[![crates.io](https://img.shields.io/crates/v/",
+            $name,
+            ")](https://crates.io/crates/",
+            $name,
+            "/",
+            $version,
+            ") [![docs.rs](https://img.shields.io/docsrs/",
+            $name,
+            "/",
+            $version,
+            ")](https://docs.rs/",
+            $name,
+            "/",
+            $version,
+            ") [![License: ",
+            $license,
+            "](https://img.shields.io/crates/l/",
+            $name,
+            "/",
+            $version,
+            ")](https://crates.io/crates/",
+            $name,
+            "/",
+            $version,
+            ")",
+            $( " ", $crate::msrv_badge!($msrv), )?
+        )
+    };
+}
+
+/// Generates a list of the metadata of the current crate: its license,
+/// repository, homepage and authors.
+///
+/// The fields are read from the `Cargo.toml` of the crate. A subset of them can
+/// be selected, in any order, by passing their names.
+///
+/// Without the `proc-macro` feature, multiple authors are separated by colons,
+/// as Cargo provides them.
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::metadata_doc!()]
+/// ```
+///
+/// ```rust
+/// #![doc = dep_doc::metadata_doc!(license, repository)]
+/// ```
+#[macro_export]
+macro_rules! metadata_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::metadata_doc_inner!(
+            [
+                $crate::core::env!("CARGO_PKG_LICENSE"),
+                $crate::core::env!("CARGO_PKG_REPOSITORY"),
+                $crate::core::env!("CARGO_PKG_HOMEPAGE"),
+                $crate::authors!(),
+            ],
+            [$($tt)*],
+        )
+    };
+}
+
+// This is just a testable version of `metadata_doc`, in which we can inject
+// specific metadata.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! metadata_doc_inner {
+    ( $metadata:tt, [] $(,)? ) => {
+        $crate::metadata_doc_inner!($metadata, [license, repository, homepage, authors])
+    };
+
+    ( $metadata:tt, [ $first:ident $(, $field:ident )* $(,)? ] $(,)? ) => {
+        concat!(
+            $crate::metadata_field!($first, $metadata),
+            $( "\n", $crate::metadata_field!($field, $metadata), )*
+        )
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! metadata_field {
+    ( license, [$license:expr, $repository:expr, $homepage:expr, $authors:expr $(,)?] ) => {
+        concat!("- **License:** ", $license)
+    };
+
+    ( repository, [$license:expr, $repository:expr, $homepage:expr, $authors:expr $(,)?] ) => {
+        concat!("- **Repository:** <", $repository, ">")
+    };
+
+    ( homepage, [$license:expr, $repository:expr, $homepage:expr, $authors:expr $(,)?] ) => {
+        concat!("- **Homepage:** <", $homepage, ">")
+    };
+
+    ( authors, [$license:expr, $repository:expr, $homepage:expr, $authors:expr $(,)?] ) => {
+        concat!("- **Authors:** ", $authors)
+    };
+
+    ( $field:ident, $metadata:tt ) => {
+        $crate::core::compile_error!(concat!(
+            "unknown metadata field `",
+            stringify!($field),
+            "`, expected one of `license`, `repository`, `homepage` or `authors`",
+        ))
+    };
+}
+
+/// Generates a `Cargo.toml` code snippet configuring how docs.rs builds the
+/// documentation of a crate, in a `[package.metadata.docs.rs]` table.
+///
+/// The keys are the ones docs.rs supports: `features`, `all-features`,
+/// `no-default-features`, `default-target`, `targets`, `rustc-args`,
+/// `rustdoc-args` and `cargo-args`. They are rendered in the order they are
+/// passed, and their values are checked at compile time. The language of the
+/// code block can be set with `@lang`, as in [`dep_doc`].
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::docsrs_metadata_doc!(
+///     all-features = true,
+///     rustdoc-args = ["--cfg", "docsrs"],
+/// )]
+/// ```
+///
+/// Unknown keys are rejected:
+///
+/// ```rust,compile_fail
+/// #![doc = dep_doc::docsrs_metadata_doc!(all_features = true)]
+/// ```
+#[macro_export]
+macro_rules! docsrs_metadata_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::split_options!(render_docsrs_metadata [] [] [] $( $tt )*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! render_docsrs_metadata {
+    ( [] [ $( $field:tt )* ] ) => {
+        concat!(
+            "```",
+            $crate::snippet_lang!([ $( $field )* ]),
+            "\n[package.metadata.docs.rs]",
+            $( $crate::docsrs_metadata_field!($field), )*
+            "\n```",
+        )
+    };
+}
+
+// Checks the value of a `[package.metadata.docs.rs]` key and renders it.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! docsrs_metadata_field {
+    ( [@lang $lang:literal] ) => {
+        ""
+    };
+
+    ( [all-features = $value:ident] ) => {
+        $crate::docsrs_metadata_field!(@bool "all-features", $value)
+    };
+
+    ( [no-default-features = $value:ident] ) => {
+        $crate::docsrs_metadata_field!(@bool "no-default-features", $value)
+    };
+
+    ( [default-target = $target:literal] ) => {
+        concat!("\n", $crate::render_key!(default-target = $target))
+    };
+
+    ( [features = [ $( $item:literal ),* $(,)? ]] ) => {
+        concat!("\n", $crate::render_key!(features = [ $( $item ),* ]))
+    };
+
+    ( [targets = [ $( $item:literal ),* $(,)? ]] ) => {
+        concat!("\n", $crate::render_key!(targets = [ $( $item ),* ]))
+    };
+
+    ( [rustc-args = [ $( $item:literal ),* $(,)? ]] ) => {
+        concat!("\n", $crate::render_key!(rustc-args = [ $( $item ),* ]))
+    };
+
+    ( [rustdoc-args = [ $( $item:literal ),* $(,)? ]] ) => {
+        concat!("\n", $crate::render_key!(rustdoc-args = [ $( $item ),* ]))
+    };
+
+    ( [cargo-args = [ $( $item:literal ),* $(,)? ]] ) => {
+        concat!("\n", $crate::render_key!(cargo-args = [ $( $item ),* ]))
+    };
+
+    ( [default-target = $( $value:tt )*] ) => {
+        $crate::core::compile_error!("`default-target` expects a string")
+    };
+
+    ( [features = $( $value:tt )*] ) => {
+        $crate::core::compile_error!("`features` expects an array of strings")
+    };
+
+    ( [targets = $( $value:tt )*] ) => {
+        $crate::core::compile_error!("`targets` expects an array of strings")
+    };
+
+    ( [rustc-args = $( $value:tt )*] ) => {
+        $crate::core::compile_error!("`rustc-args` expects an array of strings")
+    };
+
+    ( [rustdoc-args = $( $value:tt )*] ) => {
+        $crate::core::compile_error!("`rustdoc-args` expects an array of strings")
+    };
+
+    ( [cargo-args = $( $value:tt )*] ) => {
+        $crate::core::compile_error!("`cargo-args` expects an array of strings")
+    };
+
+    ( [ $first:ident $( - $rest:ident )* = $( $value:tt )* ] ) => {
+        $crate::core::compile_error!(concat!(
+            "unknown docs.rs metadata key `",
+            stringify!($first),
+            $( "-", stringify!($rest), )*
+            "`, expected one of `features`, `all-features`, `no-default-features`, ",
+            "`default-target`, `targets`, `rustc-args`, `rustdoc-args` or `cargo-args`",
+        ))
+    };
+
+    ( [ $( $tt:tt )* ] ) => {
+        $crate::core::compile_error!(concat!(
+            "expected `key = value`, found `",
+            stringify!($( $tt )*),
+            "`",
+        ))
+    };
+
+    ( @bool $key:literal, true ) => {
+        concat!("\n", $key, " = true")
+    };
+
+    ( @bool $key:literal, false ) => {
+        concat!("\n", $key, " = false")
+    };
+
+    ( @bool $key:literal, $value:ident ) => {
+        $crate::core::compile_error!(concat!("`", $key, "` expects `true` or `false`"))
+    };
+}
+
+// Expands to the authors of the current crate.
+#[cfg(feature = "proc-macro")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! authors {
+    () => {
+        $crate::dep_doc_macros::authors!()
+    };
+}
+
+// Expands to the authors of the current crate, separated by colons.
+#[cfg(not(feature = "proc-macro"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! authors {
+    () => {
+        $crate::core::env!("CARGO_PKG_AUTHORS")
+    };
+}
+
+/// Generates the URL of the documentation of the current crate version on
+/// docs.rs.
+///
+/// A path can be passed to link to a specific item. It is appended to the
+/// URL as is.
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = concat!("See the [documentation](", dep_doc::docsrs_link!(), ").")]
+/// ```
+///
+/// ```rust
+/// #![doc = concat!(
+///     "See [`dep_doc`](",
+///     dep_doc::docsrs_link!("dep_doc/macro.dep_doc.html"),
+///     ").",
+/// )]
+/// ```
+#[macro_export]
+macro_rules! docsrs_link {
+    ( $( $tt:tt )* ) => {
+        $crate::docsrs_link_inner!(
+            [$crate::core::env!("CARGO_PKG_NAME"), $crate::core::env!("CARGO_PKG_VERSION")],
+            [$($tt)*],
+        )
+    };
+}
+
+// This is just a testable version of `docsrs_link`, in which we can inject a
+// specific crate name and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! docsrs_link_inner {
+    ( [$name:expr, $version:expr], [] $(,)? ) => {
+        concat!("https://docs.rs/", $name, "/", $version)
+    };
+
+    ( [$name:expr, $version:expr], [ $path:literal $(,)? ] $(,)? ) => {
+        concat!("https://docs.rs/", $name, "/", $version, "/", $path)
+    };
+}
+
+/// Generates a link to the changelog of the current crate, as of the git tag
+/// of the version being documented.
+///
+/// The link is built from the `repository` field of the `Cargo.toml`, which is
+/// expected to be the URL of a GitHub or GitLab repository, without trailing
+/// slash. By default, the tag is the version prefixed with `v` and the
+/// changelog is `CHANGELOG.md`, at the root of the repository. Both can be
+/// changed with `tag_prefix` and `file`.
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = concat!("See [what changed](", dep_doc::changelog_link!(), ").")]
+/// ```
+///
+/// ```rust
+/// #![doc = concat!(
+///     "See [what changed](",
+///     dep_doc::changelog_link!(tag_prefix = "release-", file = "docs/CHANGES.md"),
+///     ").",
+/// )]
+/// ```
+#[macro_export]
+macro_rules! changelog_link {
+    ( $( $tt:tt )* ) => {
+        $crate::changelog_link_inner!(
+            [$crate::core::env!("CARGO_PKG_REPOSITORY"), $crate::core::env!("CARGO_PKG_VERSION")],
+            [$($tt)*],
+        )
+    };
+}
+
+// This is just a testable version of `changelog_link`, in which we can inject
+// a specific repository and version name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! changelog_link_inner {
+    ( $ctx:tt, [ $( $tt:tt )* ] $(,)? ) => {
+        $crate::split_options!(render_changelog_link $ctx [] [] $( $tt )*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! render_changelog_link {
+    ( $ctx:tt $options:tt ) => {
+        $crate::render_changelog_link!(@find $ctx ["v"] ["CHANGELOG.md"] $options)
+    };
+
+    ( @find [$repository:expr, $version:expr] [$prefix:expr] [$file:expr] [] ) => {
+        concat!($repository, "/blob/", $prefix, $version, "/", $file)
+    };
+
+    ( @find $ctx:tt $prefix:tt $file:tt [ [tag_prefix = $new:literal] $( $rest:tt )* ] ) => {
+        $crate::render_changelog_link!(@find $ctx [$new] $file [ $( $rest )* ])
+    };
+
+    ( @find $ctx:tt $prefix:tt $file:tt [ [file = $new:literal] $( $rest:tt )* ] ) => {
+        $crate::render_changelog_link!(@find $ctx $prefix [$new] [ $( $rest )* ])
+    };
+
+    ( @find $ctx:tt $prefix:tt $file:tt [ [ $( $option:tt )* ] $( $rest:tt )* ] ) => {
+        $crate::core::compile_error!(concat!(
+            "unknown `changelog_link` option `",
+            stringify!($( $option )*),
+            "`, expected `tag_prefix` or `file`",
+        ))
+    };
+}
+
+/// Generates a Rust code block importing the current crate.
+///
+/// The crate name is the one used in Rust code: the `CARGO_PKG_NAME` of the
+/// crate, in which hyphens are replaced by underscores. A path can be passed
+/// to import a specific item.
+///
+/// # Example
+///
+/// ```rust
+/// #![doc = dep_doc::use_doc!()]
+/// ```
+///
+/// ```rust
+/// #![doc = dep_doc::use_doc!("dep_doc")]
+/// ```
+#[macro_export]
+macro_rules! use_doc {
+    ( $( $tt:tt )* ) => {
+        $crate::use_doc_inner!([$crate::core::env!("CARGO_CRATE_NAME")], [$($tt)*])
+    };
+}
+
+// This is just a testable version of `use_doc`, in which we can inject a
+// specific crate name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! use_doc_inner {
+    ( [$name:expr], [] $(,)? ) => {
+        concat!("```rust\nuse ", $name, ";\n```")
+    };
+
+    ( [$name:expr], [ $path:literal $(,)? ] $(,)? ) => {
+        concat!("```rust\nuse ", $name, "::", $path, ";\n```")
+    };
+}
+
+/// Substitutes the `{name}`, `{version}` and `{repository}` placeholders of a
+/// string with the name, version and repository of the current crate.
+///
+/// This is meant for sentences of the documentation which mention the
+/// version, so that they don't need to be updated by hand. Braces which don't
+/// surround a placeholder are left as-is.
+///
+/// This macro requires the `proc-macro` feature.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #![doc = dep_doc::with_version!("Since version {version}, the builder API is stable.")]
+/// ```
+#[macro_export]
+macro_rules! with_version {
+    ( $text:literal $(,)? ) => {
+        $crate::with_version_inner!(
+            [
+                $crate::core::env!("CARGO_PKG_NAME"),
+                $crate::core::env!("CARGO_PKG_VERSION"),
+                $crate::core::env!("CARGO_PKG_REPOSITORY"),
+            ],
+            $text
+        )
+    };
+}
+
+// This is just a testable version of `with_version`, in which we can inject a
+// specific crate name, version and repository.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! with_version_inner {
+    ( [$name:expr, $version:expr, $repository:expr $(,)?], $text:literal ) => {
+        $crate::proc_macro_backend!(interpolate, [$text, [$name], [$version], [$repository]])
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! msrv_badge {
+    ( $msrv:expr ) => {
+        concat!(
+            "![MSRV](https://img.shields.io/badge/MSRV-",
+            $msrv,
+            "-blue)",
+        )
+    };
+}
+
+// Expands to the `rust-version` of the current crate.
+#[cfg(feature = "proc-macro")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! rust_version {
+    () => {
+        $crate::dep_doc_macros::rust_version!()
+    };
+}
+
+// Expands to the `rust-version` of the current crate.
+#[cfg(not(feature = "proc-macro"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! rust_version {
+    () => {
+        $crate::core::env!("CARGO_PKG_RUST_VERSION")
+    };
+}
+
+/// Declares the features of the current crate, so that [`dep_doc`] can reject
+/// the features which do not exist.
+///
+/// This macro defines a `dep_doc` macro local to the current crate. It accepts
+/// the same tokens as [`dep_doc`], and emits a compilation error when a feature
+/// passed in the `features` list is not declared. The local macro is also
+/// exported at the crate root with `pub(crate)`, so that it can be used in the
+/// crate-level documentation, which comes before the declaration.
+///
+/// As the local macro shadows [`dep_doc`], it must be invoked without the
+/// `dep_doc::` prefix. For the same reason, it can't be declared next to an
+/// `extern crate dep_doc;` item.
+///
+/// # Example
+///
+/// ```rust
+/// # extern crate dep_doc as _;
+/// dep_doc::declare_features!(["std", "serde", "async"]);
+///
+/// # fn main() {
+/// let snippet = dep_doc!(features = ["serde"]);
+/// assert!(snippet.contains(r#"features = ["serde"]"#));
+/// # }
+/// ```
+///
+/// Referring to a feature which is not declared fails:
+///
+/// ```rust,compile_fail
+/// # extern crate dep_doc as _;
+/// dep_doc::declare_features!(["std", "serde", "async"]);
+///
+/// # fn main() {
+/// let snippet = dep_doc!(features = ["serde_json"]);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! declare_features {
+    ( [ $( $feature:tt ),* $(,)? ] ) => {
+        // The local macro needs its own metavariables, hence the `$` being
+        // passed around.
+        $crate::declare_features!(@define [ $( $feature ),* ] $);
+    };
+
+    ( @define [ $( $feature:tt ),* ] $d:tt ) => {
+        macro_rules! dep_doc {
+            $(
+                ( @check_feature $feature ) => { "" };
+            )*
+
+            ( @check_feature $d other:tt ) => {
+                $crate::core::compile_error!(concat!(
+                    "feature `",
+                    $d other,
+                    "` is not declared in `declare_features!`",
+                ))
+            };
+
+            ( $d( $d tt:tt )* ) => {
+                $crate::checked_dep_doc!(dep_doc, [ $d( $d tt )* ])
+            };
+        }
+
+        #[allow(unused_imports)]
+        pub(crate) use dep_doc;
+    };
+}
+
+// Checks the features passed to a `dep_doc` macro defined by
+// `declare_features`, before rendering the snippet. Each feature is checked by
+// invoking `$check!(@check_feature "feature")`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! checked_dep_doc {
+    ( $check:ident, [ $( $tt:tt )* ] ) => {
+        $crate::split_options!(render_checked [$check [ $( $tt )* ]] [] [] $( $tt )*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! render_checked {
+    ( [$check:ident [ $( $tt:tt )* ]] [] ) => {
+        $crate::dep_doc!( $( $tt )* )
+    };
+
+    (
+        [$check:ident $tokens:tt]
+        [ [features = [ $( $feature:tt ),* $(,)? ]] $( $rest:tt )* ]
+    ) => {
+        concat!(
+            $( $check!(@check_feature $feature), )*
+            $crate::render_checked!([$check $tokens] [ $( $rest )* ]),
+        )
+    };
+
+    ( [$check:ident $tokens:tt] [ $skip:tt $( $rest:tt )* ] ) => {
+        $crate::render_checked!([$check $tokens] [ $( $rest )* ])
+    };
+}
+
+// Renders a fenced TOML block containing a single dependency table, whose
+// header is `$header` (without the brackets).
+//
+// The customization tokens are first split into comma-separated options, so
+// that the options which affect the whole snippet (such as `format_marker`)
+// can be told apart from the keys which end up in the dependency entry.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! section_inner {
+    ( [$name:expr, $version:expr $(, $components:tt)?], $header:expr, [ $( $tt:tt )* ] $(,)? ) => {
+        $crate::sections_inner!([$name, $version $(, $components)?], [$header], [ $( $tt )* ])
+    };
+}
+
+// Same as `section_inner`, but renders one dependency table per header in the
+// same code block. The tables are separated by an empty line.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! sections_inner {
+    (
+        [$name:expr, $version:expr $(, $components:tt)?],
+        [ $( $header:expr ),+ $(,)? ],
+        [ $( $tt:tt )* ] $(,)?
+    ) => {
+        $crate::split_options!(
+            render_section
+            [[$name, $version $(, $components)?], [ $( $header ),+ ]]
+            []
+            []
+            $( $tt )*
+        )
+    };
+}
+
+// Calls `$target` with the name and version of the package, read from the
+// environment variables set by cargo unless `name_env` or `version_env` name
+// other ones. The arguments between braces, each followed by a comma, are
+// passed between the package and the tokens.
+//
+// The version components can't be read for a custom version, so they expand
+// to a compile error when `precision` or `style` need them.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! with_package {
+    ( $target:ident [ $( $tt:tt )* ] ) => {
+        $crate::with_package!($target {} [ $( $tt )* ])
+    };
+
+    ( $target:ident { $( $args:tt )* } [ $( $tt:tt )* ] ) => {
+        $crate::with_package!(
+            @find $target { $( $args )* }
+            [$crate::core::env!("CARGO_PKG_NAME")]
+            []
+            [ $( $tt )* ]
+            [ $( $tt )* ]
+        )
+    };
+
+    (
+        @find $target:ident $args:tt $name:tt $version:tt
+        [ name_env = $env:literal $( $rest:tt )* ] $all:tt
+    ) => {
+        $crate::with_package!(
+            @find $target $args [$crate::core::env!($env)] $version [ $( $rest )* ] $all
+        )
+    };
+
+    (
+        @find $target:ident $args:tt $name:tt $version:tt
+        [ version_env = $env:literal $( $rest:tt )* ] $all:tt
+    ) => {
+        $crate::with_package!(@find $target $args $name [$env] [ $( $rest )* ] $all)
+    };
+
+    ( @find $target:ident $args:tt $name:tt $version:tt [ $skip:tt $( $rest:tt )* ] $all:tt ) => {
+        $crate::with_package!(@find $target $args $name $version [ $( $rest )* ] $all)
+    };
+
+    ( @find $target:ident { $( $args:tt )* } [$name:expr] [] [] [ $( $all:tt )* ] ) => {
+        $crate::$target!(
+            [$name, $crate::core::env!("CARGO_PKG_VERSION")],
+            $( $args )*
+            [ $( $all )* ],
+        )
+    };
+
+    ( @find $target:ident { $( $args:tt )* } [$name:expr] [$env:literal] [] [ $( $all:tt )* ] ) => {
+        $crate::$target!(
+            [
+                $name,
+                $crate::core::env!($env),
+                [
+                    $crate::with_package!(@component),
+                    $crate::with_package!(@component),
+                    $crate::with_package!(@component),
+                    $crate::with_package!(@component),
+                ]
+            ],
+            $( $args )*
+            [ $( $all )* ],
+        )
+    };
+
+    ( @component ) => {
+        $crate::core::compile_error!(
+            "the version read from `version_env` can only be rendered as-is or with `style = exact`"
+        )
+    };
+}
+
+// Splits a token stream on its top-level commas, wraps each option in
+// brackets, and forwards the resulting list to `$target`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! split_options {
+    // Fast path for the common `key = value` options, which keeps the
+    // recursion shallow.
+    (
+        $target:ident $ctx:tt [ $( $done:tt )* ] []
+        $key:ident = $value:tt $(, $( $rest:tt )* )?
+    ) => {
+        $crate::split_options!(
+            $target $ctx [ $( $done )* [$key = $value] ] [] $( $( $rest )* )?
+        )
+    };
+
+    ( $target:ident $ctx:tt [ $( $done:tt )* ] [] ) => {
+        $crate::$target!($ctx [ $( $done )* ])
+    };
+
+    ( $target:ident $ctx:tt [ $( $done:tt )* ] [ $( $current:tt )+ ] ) => {
+        $crate::$target!($ctx [ $( $done )* [ $( $current )+ ] ])
+    };
+
+    ( $target:ident $ctx:tt [ $( $done:tt )* ] [ $( $current:tt )* ] , $( $rest:tt )* ) => {
+        $crate::split_options!($target $ctx [ $( $done )* [ $( $current )* ] ] [] $( $rest )*)
+    };
+
+    ( $target:ident $ctx:tt [ $( $done:tt )* ] [ $( $current:tt )* ] $next:tt $( $rest:tt )* ) => {
+        $crate::split_options!($target $ctx [ $( $done )* ] [ $( $current )* $next ] $( $rest )*)
+    };
+}
+
+// Renders the header of the `$table` table specific to the targets matching
+// `cfg($cfg)`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! target_header {
+    ( $cfg:expr, $table:expr $(,)? ) => {
+        concat!("target.'cfg(", $cfg, ")'.", $table)
+    };
+}
+
+// Expands to an empty string if the tokens form a valid `cfg` predicate, and
+// to a compilation error otherwise.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! check_cfg_predicate {
+    ( all ( $( $predicates:tt )* ) ) => {
+        $crate::split_options!(check_cfg_predicates [] [] [] $( $predicates )*)
+    };
+
+    ( any ( $( $predicates:tt )* ) ) => {
+        $crate::split_options!(check_cfg_predicates [] [] [] $( $predicates )*)
+    };
+
+    ( not ( $( $predicate:tt )* ) ) => {
+        $crate::check_cfg_predicate!( $( $predicate )* )
+    };
+
+    ( $key:ident ) => {
+        ""
+    };
+
+    ( $key:ident = $value:literal ) => {
+        ""
+    };
+
+    ( $( $tt:tt )* ) => {
+        $crate::core::compile_error!(concat!(
+            "invalid `cfg` predicate: `",
+            stringify!( $( $tt )* ),
+            "`",
+        ))
+    };
+}
+
+// Expands to an empty string if the string is a valid `cfg(...)` predicate,
+// and to a compilation error otherwise.
+#[cfg(feature = "proc-macro")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! check_cfg_string {
+    ( $predicate:literal ) => {
+        $crate::dep_doc_macros::cfg_predicate!([[$crate] $predicate])
+    };
+}
+
+// The content of the string can't be checked without the `proc-macro`
+// feature.
+#[cfg(not(feature = "proc-macro"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! check_cfg_string {
+    ( $predicate:literal ) => {
+        ""
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! check_cfg_predicates {
+    ( [] [ $( [ $( $predicate:tt )* ] )* ] ) => {
+        concat!( $( $crate::check_cfg_predicate!( $( $predicate )* ), )* )
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! render_section {
+    ( [ $package:tt, $headers:tt ] $options:tt ) => {
+        $crate::render_section!(@enabled_features [ $package, $headers ] $options $options)
+    };
+
+    // `enabled_features` is replaced by the `features` option by the
+    // `enabled_features` procedural macro, which renders the section again.
+    ( @enabled_features [ $package:tt, $headers:tt ] $options:tt [] ) => {
+        $crate::render_section!(@mode $package, $headers, [] [] $options)
+    };
+
+    (
+        @enabled_features $ctx:tt $options:tt
+        [ [enabled_features $( ( $( $args:tt )* ) )?] $( $rest:tt )* ]
+    ) => {
+        $crate::proc_macro_backend!(
+            enabled_features,
+            [ [$crate] $ctx $options [ $( $( $args )* )? ] ]
+        )
+    };
+
+    ( @enabled_features $ctx:tt $options:tt [ $skip:tt $( $rest:tt )* ] ) => {
+        $crate::render_section!(@enabled_features $ctx $options [ $( $rest )* ])
+    };
+
+    ( @mode $package:tt, $headers:tt, [] $options:tt [] ) => {
+        $crate::render_section!(
+            @indented
+            $crate::snippet_indent!($options),
+            $package,
+            $headers
+            $options
+        )
+    };
+
+    // `@html` is moved after the other options, so that the renderers which
+    // stop at the option they look for still find it in the remaining ones.
+    //
+    // The content is escaped by the `html` procedural macro, which rewrites
+    // the string literals it is made of.
+    ( @mode $package:tt, $headers:tt, [@html] [ $( $option:tt )* ] [] ) => {
+        concat!(
+            $crate::verify!(verify_features, [ $( $option )* ]),
+            $crate::verify!(verify_git, [ $( $option )* ]),
+            $crate::proc_macro_backend!(
+                html,
+                [
+                    $crate::render_section!(
+                        @content "",
+                        $package,
+                        $headers
+                        [ $( $option )* [@html] ]
+                    )
+                ]
+            ),
+        )
+    };
+
+    ( @mode $package:tt, $headers:tt, $html:tt $options:tt [ [@html] $( $rest:tt )* ] ) => {
+        $crate::render_section!(@mode $package, $headers, [@html] $options [ $( $rest )* ])
+    };
+
+    (
+        @mode $package:tt,
+        $headers:tt,
+        $html:tt
+        [ $( $option:tt )* ]
+        [ $next:tt $( $rest:tt )* ]
+    ) => {
+        $crate::render_section!(
+            @mode $package,
+            $headers,
+            $html
+            [ $( $option )* $next ]
+            [ $( $rest )* ]
+        )
+    };
+
+    ( @indented $indent:expr, $package:tt, $headers:tt $options:tt ) => {
+        concat!(
+            $crate::verify!(verify_features, $options),
+            $crate::verify!(verify_git, $options),
+            $crate::snippet_option!(leading_blank_line, $indent, $options),
+            $crate::snippet_heading!($indent, $package, $options),
+            $crate::snippet_option!(details_open, $indent, $options),
+            $indent,
+            "```",
+            $crate::snippet_lang!($options),
+            "\n",
+            $crate::render_section!(@content $indent, $package, $headers $options),
+            "\n",
+            $indent,
+            "```",
+            $crate::snippet_option!(details_close, $indent, $options),
+        )
+    };
+
+    (
+        @content $indent:expr,
+        $package:tt,
+        [ $first:expr $(, $header:expr )* ]
+        $options:tt
+    ) => {
+        concat!(
+            $crate::snippet_option!(format_marker, $indent, $options),
+            $crate::render_table!($indent, $package, $first, $options),
+            $(
+                $crate::snippet_blank_line!($options),
+                $crate::render_table!($indent, $package, $header, $options),
+            )*
+            $crate::features_table!($indent, $package, $options),
+            $crate::snippet_raw!($indent, $options),
+        )
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! render_table {
+    (
+        @form $form:ident
+        [
+            $indent:expr,
+            [$name:expr, $version:expr $(, $components:tt)?],
+            $header:expr,
+            [ $( $option:tt )* ]
+        ]
+    ) => {
+        concat!(
+            $indent,
+            "[",
+            $crate::table_header!(
+                $form,
+                $header,
+                $crate::entry_key!($name, [ $( $option )* ])
+            ),
+            "]\n",
+            $crate::snippet_option!(compat_matrix, $indent, [ $( $option )* ]),
+            $crate::snippet_option!(links, $indent, [ $( $option )* ]),
+            $crate::snippet_option!(rev_note, $indent, [ $( $option )* ]),
+            $crate::snippet_option!(recommended_features, $indent, [ $( $option )* ]),
+            $crate::snippet_option!(no_std, $indent, [ $( $option )* ]),
+            $crate::snippet_comment!($indent, $version, [ $( $option )* ]),
+            $indent,
+            $crate::render_entry!(
+                [
+                    $name,
+                    [
+                        [
+                            @string "version" = $crate::snippet_version!(
+                                [$name, $version $(, $components)?],
+                                [ $( $option )* ]
+                            )
+                        ]
+                    ],
+                    $indent,
+                    $form
+                ]
+                []
+                [ $( $option )* ]
+            ),
+            $crate::companions!(
+                $form,
+                $indent,
+                $header,
+                [$name, $version $(, $components)?],
+                [ $( $option )* ]
+            ),
+            $crate::peers!($form, $indent, $header, [ $( $option )* ]),
+        )
+    };
+
+    // Called by `split_options`, for `sections_doc`.
+    ( [$indent:expr, $package:tt, $header:expr] $options:tt ) => {
+        $crate::render_table!($indent, $package, $header, $options)
+    };
+
+    ( $indent:expr, $package:tt, $header:expr, $options:tt ) => {
+        $crate::entry_form!(render_table [$indent, $package, $header, $options] $options)
+    };
+}
+
+// Looks up the `form` option and forwards it to `$target`, with `$ctx`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! entry_form {
+    ( $target:ident $ctx:tt [] ) => {
+        $crate::$target!(@form inline $ctx)
+    };
+
+    ( $target:ident $ctx:tt [ [form = $form:ident] $( $rest:tt )* ] ) => {
+        $crate::$target!(@form $form $ctx)
+    };
+
+    ( $target:ident $ctx:tt [ $skip:tt $( $rest:tt )* ] ) => {
+        $crate::entry_form!($target $ctx [ $( $rest )* ])
+    };
+}
+
+// Renders the header of the table holding the dependency. With the `table`
+// form, the dependency gets its own table.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! table_header {
+    ( inline, $header:expr, $key:expr ) => {
+        $header
+    };
+
+    ( multiline, $header:expr, $key:expr ) => {
+        $header
+    };
+
+    ( table, $header:expr, $key:expr ) => {
+        concat!($header, ".", $key)
+    };
+
+    ( $form:ident, $header:expr, $key:expr ) => {
+        $crate::core::compile_error!(concat!(
+            "unknown form `",
+            stringify!($form),
+            "`, expected `inline`, `multiline` or `table`",
+        ))
+    };
+}
+
+// Expands to the key of the dependency, which is the crate name unless it is
+// renamed with `as`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! entry_key {
+    ( $name:expr, [] ) => {
+        $name
+    };
+
+    ( $name:expr, [ [as = $alias:literal] $( $rest:tt )* ] ) => {
+        $alias
+    };
+
+    ( $name:expr, [ $skip:tt $( $rest:tt )* ] ) => {
+        $crate::entry_key!($name, [ $( $rest )* ])
+    };
+}
+
+// Renders the companion crates listed in the `with` option, with the same
+// version requirement as the current crate. Each of them gets its own line,
+// or its own table with the `table` form.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! companions {
+    ( $form:ident, $indent:expr, $header:expr, $package:tt, $options:tt ) => {
+        $crate::companions!(@find $form, $indent, $header, $package, $options $options)
+    };
+
+    ( @find $form:ident, $indent:expr, $header:expr, $package:tt, $options:tt [] ) => {
+        ""
+    };
+
+    (
+        @find inline,
+        $indent:expr,
+        $header:expr,
+        $package:tt,
+        $options:tt
+        [ [with = [ $( $companion:literal ),* $(,)? ]] $( $rest:tt )* ]
+    ) => {
+        concat!(
+            $(
+                "\n",
+                $indent,
+                $crate::package_import!(
+                    [$companion, $crate::snippet_version!($package, $options)],
+                    []
+                ),
+            )*
+        )
+    };
+
+    (
+        @find table,
+        $indent:expr,
+        $header:expr,
+        $package:tt,
+        $options:tt
+        [ [with = [ $( $companion:literal ),* $(,)? ]] $( $rest:tt )* ]
+    ) => {
+        concat!(
+            $(
+                $crate::snippet_blank_line!($options),
+                $indent,
+                "[",
+                $header,
+                ".",
+                $companion,
+                "]\n",
+                $indent,
+                $crate::render_key!(@string "version" = $crate::snippet_version!($package, $options)),
+            )*
+        )
+    };
+
+    (
+        @find $form:ident,
+        $indent:expr,
+        $header:expr,
+        $package:tt,
+        $options:tt
+        [ $skip:tt $( $rest:tt )* ]
+    ) => {
+        $crate::companions!(@find $form, $indent, $header, $package, $options [ $( $rest )* ])
+    };
+}
+
+// Puts the `branch`, `tag` or `rev` key of a git dependency right after its
+// `git` key, and renders the entry.
+//
+// At most one of these keys can be specified. When `git` is missing, it
+// defaults to the repository of the crate.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! git_keys {
+    ( $package:tt [] $git:tt [ [branch = $value:tt] $( $rest:tt )* ] $keys:tt ) => {
+        $crate::git_keys!($package [[branch = $value]] $git [ $( $rest )* ] $keys)
+    };
+
+    ( $package:tt [] $git:tt [ [tag = $value:tt] $( $rest:tt )* ] $keys:tt ) => {
+        $crate::git_keys!($package [[tag = $value]] $git [ $( $rest )* ] $keys)
+    };
+
+    ( $package:tt [] $git:tt [ [rev = $value:tt] $( $rest:tt )* ] $keys:tt ) => {
+        $crate::git_keys!($package [[rev = $value]] $git [ $( $rest )* ] $keys)
+    };
+
+    ( $package:tt [$reference:tt] $git:tt [ [branch = $value:tt] $( $rest:tt )* ] $keys:tt ) => {
+        $crate::git_keys!(@conflict)
+    };
+
+    ( $package:tt [$reference:tt] $git:tt [ [tag = $value:tt] $( $rest:tt )* ] $keys:tt ) => {
+        $crate::git_keys!(@conflict)
+    };
+
+    ( $package:tt [$reference:tt] $git:tt [ [rev = $value:tt] $( $rest:tt )* ] $keys:tt ) => {
+        $crate::git_keys!(@conflict)
+    };
+
+    ( $package:tt $reference:tt [] [ [git = $value:tt] $( $rest:tt )* ] $keys:tt ) => {
+        $crate::git_keys!($package $reference [git] [ $( $rest )* ] $keys)
+    };
+
+    ( $package:tt $reference:tt $git:tt [ $skip:tt $( $rest:tt )* ] $keys:tt ) => {
+        $crate::git_keys!($package $reference $git [ $( $rest )* ] $keys)
+    };
+
+    ( $package:tt $reference:tt $git:tt [] [ $( $key:tt )* ] ) => {
+        $crate::git_keys!(@place $package $reference $git [] [ $( $key )* ])
+    };
+
+    (
+        @place $package:tt [ $( $reference:tt )? ] $git:tt
+        [ $( $done:tt )* ]
+        [ [git = $value:tt] $( $rest:tt )* ]
+    ) => {
+        $crate::git_keys!(
+            @place $package [ $( $reference )? ] $git
+            [ $( $done )* [git = $value] $( $reference )? ]
+            [ $( $rest )* ]
+        )
+    };
+
+    (
+        @place $package:tt [ $( $reference:tt )? ] $git:tt
+        [ $( $done:tt )* ]
+        [ [@string "git" = $value:expr] $( $rest:tt )* ]
+    ) => {
+        $crate::git_keys!(
+            @place $package [ $( $reference )? ] $git
+            [ $( $done )* [@string "git" = $value] $( $reference )? ]
+            [ $( $rest )* ]
+        )
+    };
+
+    ( @place $package:tt $reference:tt $git:tt $done:tt [ [branch = $value:tt] $( $rest:tt )* ] ) => {
+        $crate::git_keys!(@reference $package $reference $git $done [ $( $rest )* ])
+    };
+
+    ( @place $package:tt $reference:tt $git:tt $done:tt [ [tag = $value:tt] $( $rest:tt )* ] ) => {
+        $crate::git_keys!(@reference $package $reference $git $done [ $( $rest )* ])
+    };
+
+    ( @place $package:tt $reference:tt $git:tt $done:tt [ [rev = $value:tt] $( $rest:tt )* ] ) => {
+        $crate::git_keys!(@reference $package $reference $git $done [ $( $rest )* ])
+    };
+
+    ( @place $package:tt $reference:tt $git:tt [ $( $done:tt )* ] [ $key:tt $( $rest:tt )* ] ) => {
+        $crate::git_keys!(@place $package $reference $git [ $( $done )* $key ] [ $( $rest )* ])
+    };
+
+    ( @place $package:tt $reference:tt $git:tt $done:tt [] ) => {
+        $crate::git_keys!(@render $package $done)
+    };
+
+    // The reference was already placed after `git`.
+    ( @reference $package:tt $reference:tt [git] $done:tt $rest:tt ) => {
+        $crate::git_keys!(@place $package $reference [git] $done $rest)
+    };
+
+    // Without `git`, the repository of the crate is placed before the
+    // reference.
+    ( @reference $package:tt [$reference:tt] [] [ $( $done:tt )* ] $rest:tt ) => {
+        $crate::git_keys!(
+            @place $package [$reference] []
+            [
+                $( $done )*
+                [@string "git" = $crate::core::env!("CARGO_PKG_REPOSITORY")]
+                $reference
+            ]
+            $rest
+        )
+    };
+
+    ( @render [$name:expr, [ $( $version:tt )? ], $indent:expr, inline] [ $( $key:tt )* ] ) => {
+        $crate::inline_table!($name, [ $( $version )? $( $key )* ])
+    };
+
+    ( @render [$name:expr, [ $( $version:tt )? ], $indent:expr, multiline] [ $( $key:tt )* ] ) => {
+        $crate::multiline_table!($indent, $name, [ $( $version )? $( $key )* ])
+    };
+
+    ( @render [$name:expr, [ $( $version:tt )? ], $indent:expr, table] [ $( $key:tt )* ] ) => {
+        $crate::table_fields!($indent, [ $( $version )? $( $key )* ])
+    };
+
+    // Unknown forms are reported by `table_header`.
+    ( @render [$name:expr, $version:tt, $indent:expr, $form:ident] $keys:tt ) => {
+        ""
+    };
+
+    ( @conflict ) => {
+        $crate::core::compile_error!("only one of `branch`, `tag` and `rev` can be specified")
+    };
+}
+
+// Renders the `peer(...)` dependencies the user crate must add as well, in the
+// order they are passed.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! peers {
+    ( $form:ident, $indent:expr, $header:expr, [] ) => {
+        ""
+    };
+
+    (
+        $form:ident,
+        $indent:expr,
+        $header:expr,
+        [ [peer ( $name:literal $(, $( $field:tt )* )? )] $( $rest:tt )* ]
+    ) => {
+        concat!(
+            $crate::split_options!(
+                peer_fields
+                [$form, $indent, $header, $name, $crate::snippet_blank_line!([ $( $rest )* ])]
+                []
+                []
+                $( $( $field )* )?
+            ),
+            $crate::peers!($form, $indent, $header, [ $( $rest )* ]),
+        )
+    };
+
+    ( $form:ident, $indent:expr, $header:expr, [ [peer $( $args:tt )*] $( $rest:tt )* ] ) => {
+        $crate::core::compile_error!(concat!(
+            "expected `peer(\"name\", key = value, ...)`, found `peer",
+            stringify!($( $args )*),
+            "`",
+        ))
+    };
+
+    ( $form:ident, $indent:expr, $header:expr, [ $skip:tt $( $rest:tt )* ] ) => {
+        $crate::peers!($form, $indent, $header, [ $( $rest )* ])
+    };
+}
+
+// Translates the shorthand flags of a peer into the keys they stand for, as
+// `render_entry` does for the main entry, and forwards them to `render_peer`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! peer_fields {
+    ( $ctx:tt $fields:tt ) => {
+        $crate::peer_fields!(@translate $ctx [] $fields)
+    };
+
+    ( @translate $ctx:tt $done:tt [] ) => {
+        $crate::render_peer!($ctx $done)
+    };
+
+    ( @translate $ctx:tt [ $( $done:tt )* ] [ [optional] $( $rest:tt )* ] ) => {
+        $crate::peer_fields!(@translate $ctx [ $( $done )* [optional = true] ] [ $( $rest )* ])
+    };
+
+    (
+        @translate $ctx:tt [ $( $done:tt )* ]
+        [ [no_default_features] $( $rest:tt )* ]
+    ) => {
+        $crate::peer_fields!(
+            @translate $ctx [ $( $done )* [default-features = false] ] [ $( $rest )* ]
+        )
+    };
+
+    ( @translate $ctx:tt [ $( $done:tt )* ] [ [no_std] $( $rest:tt )* ] ) => {
+        $crate::peer_fields!(
+            @translate $ctx [ $( $done )* [default-features = false] ] [ $( $rest )* ]
+        )
+    };
+
+    ( @translate $ctx:tt [ $( $done:tt )* ] [ $field:tt $( $rest:tt )* ] ) => {
+        $crate::peer_fields!(@translate $ctx [ $( $done )* $field ] [ $( $rest )* ])
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! render_peer {
+    ( [$form:ident, $indent:expr, $header:expr, $name:literal, $blank:expr] [] ) => {
+        $crate::core::compile_error!(concat!(
+            "peer `",
+            $name,
+            "` needs a `version`, a `git` repository or a `path`",
+        ))
+    };
+
+    (
+        [inline, $indent:expr, $header:expr, $name:literal, $blank:expr]
+        [ [version = $version:literal] ]
+    ) => {
+        concat!(
+            "\n",
+            $indent,
+            $crate::package_import!([$name, $version], [])
+        )
+    };
+
+    ( [inline, $indent:expr, $header:expr, $name:literal, $blank:expr] $fields:tt ) => {
+        concat!("\n", $indent, $crate::inline_table!($name, $fields))
+    };
+
+    (
+        [multiline, $indent:expr, $header:expr, $name:literal, $blank:expr]
+        [ [version = $version:literal] ]
+    ) => {
+        concat!(
+            "\n",
+            $indent,
+            $crate::package_import!([$name, $version], [])
+        )
+    };
+
+    ( [multiline, $indent:expr, $header:expr, $name:literal, $blank:expr] $fields:tt ) => {
+        concat!(
+            "\n",
+            $indent,
+            $crate::multiline_table!($indent, $name, $fields)
+        )
+    };
+
+    ( [table, $indent:expr, $header:expr, $name:literal, $blank:expr] $fields:tt ) => {
+        concat!(
+            $blank,
+            $indent,
+            "[",
+            $header,
+            ".",
+            $name,
+            "]\n",
+            $indent,
+            $crate::table_fields!($indent, $fields),
+        )
+    };
+
+    ( [$form:ident, $indent:expr, $header:expr, $name:literal, $blank:expr] $fields:tt ) => {
+        ""
+    };
+}
+
+// Renders the `[features]` table of the user crate, when the dependency is
+// enabled by one of its features (`optional_feature`).
+#[doc(hidden)]
+#[macro_export]
+macro_rules! features_table {
+    ( $indent:expr, $package:tt, $options:tt ) => {
+        $crate::features_table!(@find $indent, $package, $options $options)
+    };
+
+    ( @find $indent:expr, $package:tt, $options:tt [] ) => {
+        ""
+    };
+
+    // The feature enables the dependency by its key, which is the alias when
+    // `as` is passed.
+    (
+        @find $indent:expr,
+        [$name:expr, $version:expr $(, $components:tt)?],
+        $options:tt
+        [ [optional_feature = $feature:literal] $( $rest:tt )* ]
+    ) => {
+        concat!(
+            $crate::snippet_blank_line!([ $( $rest )* ]),
+            $indent,
+            "[features]\n",
+            $indent,
+            "# The `",
+            $feature,
+            "` feature enables the optional `",
+            $crate::entry_key!($name, $options),
+            "` dependency.\n",
+            $indent,
+            $feature,
+            " = [\"dep:",
+            $crate::entry_key!($name, $options),
+            "\"]",
+        )
+    };
+
+    ( @find $indent:expr, $package:tt, $options:tt [ $skip:tt $( $rest:tt )* ] ) => {
+        $crate::features_table!(@find $indent, $package, $options [ $( $rest )* ])
+    };
+}
+
+// Renders the dependency entry, skipping the snippet-level options.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! render_entry {
+    (
+        [$name:expr, [ [@string "version" = $version:expr] ], $indent:expr, inline]
+        []
+        []
+    ) => {
+        $crate::package_import!([$name, $version], [])
+    };
+
+    (
+        [$name:expr, [ [@string "version" = $version:expr] ], $indent:expr, multiline]
+        []
+        []
+    ) => {
+        $crate::package_import!([$name, $version], [])
+    };
+
+    ( [$name:expr, [], $indent:expr, $form:ident] [] [] ) => {
+        $crate::core::compile_error!(
+            "`version = false` requires another source for the dependency, such as `git` or `path`"
+        )
+    };
+
+    ( $package:tt [ $( $key:tt )* ] [] ) => {
+        $crate::git_keys!($package [] [] [ $( $key )* ] [ $( $key )* ])
+    };
+
+    (
+        [$name:expr, $version:tt, $indent:expr, $form:ident]
+        [ $( $keys:tt )* ]
+        [ [version = false] $( $rest:tt )* ]
+    ) => {
+        $crate::render_entry!([$name, [], $indent, $form] [ $( $keys )* ] [ $( $rest )* ])
+    };
+
+    (
+        [$name:expr, $version:tt, $indent:expr, $form:ident]
+        [ $( $keys:tt )* ]
+        [ [version = $requirement:literal] $( $rest:tt )* ]
+    ) => {
+        $crate::render_entry!(
+            [$name, [ [@string "version" = $requirement] ], $indent, $form]
+            [ $( $keys )* ]
+            [ $( $rest )* ]
+        )
+    };
+
+    ( $package:tt [ $( $keys:tt )* ] [ [version_position = first] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+
+    // The version is moved after the other keys once every option is
+    // processed, so that `version = ...` can still replace or remove it.
+    ( $package:tt [ $( $keys:tt )* ] [ [version_position = last] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* [@version_last] ])
+    };
+
+    // A lone version is still rendered as `name = "version"`.
+    ( $package:tt [] [ [@version_last] ] ) => {
+        $crate::render_entry!($package [] [])
+    };
+
+    (
+        [$name:expr, [ $( $version:tt )? ], $indent:expr, $form:ident]
+        [ $( $keys:tt )* ]
+        [ [@version_last] ]
+    ) => {
+        $crate::render_entry!([$name, [], $indent, $form] [ $( $keys )* $( $version )? ] [])
+    };
+
+    ( $package:tt [ $( $keys:tt )* ] [ [version_position = $position:ident] $( $rest:tt )* ] ) => {
+        $crate::core::compile_error!(concat!(
+            "unknown version position `",
+            stringify!($position),
+            "`, expected `first` or `last`",
+        ))
+    };
+
+    ( $package:tt [ $( $keys:tt )* ] [ [form = $form:ident] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+
+    ( $package:tt [ $( $keys:tt )* ] [ [format_marker] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+
+    ( $package:tt [ $( $keys:tt )* ] [ [leading_blank_line] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+
+    (
+        [$name:expr, $version:tt, $indent:expr, $form:ident]
+        [ $( $keys:tt )* ]
+        [ [as = $alias:literal] $( $rest:tt )* ]
+    ) => {
+        $crate::render_entry!(
+            [$alias, $version, $indent, $form]
+            [ [@string "package" = $name] $( $keys )* ]
+            [ $( $rest )* ]
+        )
+    };
+
+    ( $package:tt [ $( $keys:tt )* ] [ [with = $companions:tt] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+
+    ( $package:tt [ $( $keys:tt )* ] [ [verify_features] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+    ( $package:tt [ $( $keys:tt )* ] [ [verify_git] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+
+    ( $package:tt [ $( $keys:tt )* ] [ [block_indent = $indent:tt] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+    ( $package:tt [ $( $keys:tt )* ] [ [@lang $lang:literal] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+    ( $package:tt [ $( $keys:tt )* ] [ [heading] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+    ( $package:tt [ $( $keys:tt )* ] [ [details $( = $summary:literal )?] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+    ( $package:tt [ $( $keys:tt )* ] [ [@html] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+    ( $package:tt [ $( $keys:tt )* ] [ [raw = $raw:tt] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+    ( $package:tt [ $( $keys:tt )* ] [ [comment = $comment:tt] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+    ( $package:tt [ $( $keys:tt )* ] [ [peer $args:tt] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+    ( $package:tt [ $( $keys:tt )* ] [ [name_env = $env:literal] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+    ( $package:tt [ $( $keys:tt )* ] [ [version_env = $env:literal] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+
+    ( $package:tt [ $( $keys:tt )* ] [ [style = $style:tt] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+
+    ( $package:tt [ $( $keys:tt )* ] [ [precision = $precision:tt] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+    ( $package:tt [ $( $keys:tt )* ] [ [prerelease = $prerelease:tt] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+
+    ( $package:tt [ $( $keys:tt )* ] [ [rev_note = $note:literal] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+
+    ( $package:tt [ $( $keys:tt )* ] [ [compat_matrix = $matrix:tt] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+
+    ( $package:tt [ $( $keys:tt )* ] [ [links = $library:literal] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* ] [ $( $rest )* ])
+    };
+
+    (
+        $package:tt
+        [ $( $keys:tt )* ]
+        [ [optional_feature = $feature:literal] $( $rest:tt )* ]
+    ) => {
+        $crate::render_entry!($package [ $( $keys )* [optional = true] ] [ $( $rest )* ])
+    };
+
+    (
+        $package:tt
+        [ $( $keys:tt )* ]
+        [ [recommended_features = $features:tt] $( $rest:tt )* ]
+    ) => {
+        $crate::render_entry!(
+            $package
+            [ $( $keys )* [default-features = false] [features = $features] ]
+            [ $( $rest )* ]
+        )
+    };
+
+    ( $package:tt [ $( $keys:tt )* ] [ [relative_path] $( $rest:tt )* ] ) => {
+        $crate::render_entry!(
+            $package
+            [ $( $keys )* [@string "path" = $crate::proc_macro_backend!(relative_path, [])] ]
+            [ $( $rest )* ]
+        )
+    };
+    (
+        $package:tt
+        [ $( $keys:tt )* ]
+        [ [relative_path = $base:literal] $( $rest:tt )* ]
+    ) => {
+        $crate::render_entry!(
+            $package
+            [ $( $keys )* [@string "path" = $crate::proc_macro_backend!(relative_path, [$base])] ]
+            [ $( $rest )* ]
+        )
+    };
+
+    ( $package:tt [ $( $keys:tt )* ] [ [no_default_features] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* [default-features = false] ] [ $( $rest )* ])
+    };
+    ( $package:tt [ $( $keys:tt )* ] [ [optional] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* [optional = true] ] [ $( $rest )* ])
+    };
+
+    ( $package:tt [ $( $keys:tt )* ] [ [no_std] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* [default-features = false] ] [ $( $rest )* ])
+    };
+    ( $package:tt [ $( $keys:tt )* ] [ [no_std = $features:tt] $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* [default-features = false] ] [ $( $rest )* ])
+    };
+
+    ( $package:tt [ $( $keys:tt )* ] [ $key:tt $( $rest:tt )* ] ) => {
+        $crate::render_entry!($package [ $( $keys )* $key ] [ $( $rest )* ])
+    };
+}
+
+// Runs the `$check` verification of `dep_doc_macros` when the option of the
+// same name is passed. Expands to an empty string.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! verify {
+    ( $check:ident, $options:tt ) => {
+        $crate::verify!(@find $check, $options $options)
+    };
+
+    ( @find $check:ident, $options:tt [] ) => {
+        ""
+    };
+
+    ( @find verify_features, $options:tt [ [verify_features] $( $rest:tt )* ] ) => {
+        $crate::proc_macro_backend!(verify_features, $options)
+    };
+
+    ( @find verify_git, $options:tt [ [verify_git] $( $rest:tt )* ] ) => {
+        $crate::proc_macro_backend!(verify_git, $options)
+    };
+
+    ( @find $check:ident, $options:tt [ $skip:tt $( $rest:tt )* ] ) => {
+        $crate::verify!(@find $check, $options [ $( $rest )* ])
+    };
+}
+
+// Forwards to the `$macro` procedural macro of `dep_doc_macros`, or reports
+// that the `proc-macro` feature is needed.
+#[cfg(feature = "proc-macro")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! proc_macro_backend {
+    ( @item $macro:ident, $input:tt ) => {
+        $crate::dep_doc_macros::$macro!($input);
+    };
+
+    ( $macro:ident, $input:tt ) => {
+        $crate::dep_doc_macros::$macro!($input)
+    };
+}
+
+#[cfg(not(feature = "proc-macro"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! proc_macro_backend {
+    ( @item $macro:ident, $input:tt ) => {
+        $crate::proc_macro_backend!($macro, $input);
+    };
+
+    ( $macro:ident, $input:tt ) => {
+        $crate::core::compile_error!(concat!(
+            "`",
+            stringify!($macro),
+            "` requires the `proc-macro` feature of `dep_doc`",
+        ))
+    };
+}
+
+// Renders the sentence introducing the snippet, with a link to the crates.io
+// page of the crate, when `heading` is passed.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! snippet_heading {
+    ( $indent:expr, [$name:expr, $( $version:tt )*], [ [heading] $( $rest:tt )* ] ) => {
+        concat!(
+            $indent,
+            "Add [`",
+            $name,
+            "`](https://crates.io/crates/",
+            $name,
+            ") to your `Cargo.toml`:\n\n",
+        )
+    };
+
+    ( $indent:expr, $package:tt, [] ) => {
+        ""
+    };
+
+    ( $indent:expr, $package:tt, [ $skip:tt $( $rest:tt )* ] ) => {
+        $crate::snippet_heading!($indent, $package, [ $( $rest )* ])
+    };
+}
+
+// Looks up a snippet-level option and renders its contribution to the
+// snippet.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! snippet_option {
+    ( format_marker, $indent:expr, [ [format_marker] $( $rest:tt )* ] ) => {
+        concat!($indent, "# dep-doc-format: v1\n")
+    };
+
+    ( leading_blank_line, $indent:expr, [ [leading_blank_line] $( $rest:tt )* ] ) => {
+        "\n"
+    };
+
+    (
+        recommended_features,
+        $indent:expr,
+        [ [recommended_features = $features:tt] $( $rest:tt )* ]
+    ) => {
+        concat!($indent, "# recommended minimal set\n")
+    };
+
+    (
+        compat_matrix,
+        $indent:expr,
+        [
+            [compat_matrix = [ $( ($left:literal, $right:literal) ),* $(,)? ]]
+            $( $rest:tt )*
+        ]
+    ) => {
+        concat!(
+            $indent,
+            "# compatibility matrix:\n",
+            $crate::compat_matrix_lines!($indent, [ $( ($left, $right) ),* ]),
+        )
+    };
+
+    (
+        no_std,
+        $indent:expr,
+        [ [no_std = [ $first:literal $(, $feature:literal )* $(,)? ]] $( $rest:tt )* ]
+    ) => {
+        concat!(
+            $indent,
+            "# requires the standard library: `",
+            $first,
+            "`",
+            $( ", `", $feature, "`", )*
+            "\n",
+        )
+    };
+
+    ( details_open, $indent:expr, [ [details] $( $rest:tt )* ] ) => {
+        concat!($indent, "<details><summary>Cargo.toml</summary>\n\n")
+    };
+
+    ( details_open, $indent:expr, [ [details = $summary:literal] $( $rest:tt )* ] ) => {
+        concat!($indent, "<details><summary>", $summary, "</summary>\n\n")
+    };
+
+    ( details_close, $indent:expr, [ [details $( = $summary:literal )?] $( $rest:tt )* ] ) => {
+        concat!("\n\n", $indent, "</details>")
+    };
+
+    ( links, $indent:expr, [ [links = $library:literal] $( $rest:tt )* ] ) => {
+        concat!(
+            $indent,
+            "# links to native library: ",
+            $crate::comment_text!("links", $library),
+            "\n",
+        )
+    };
+
+    ( rev_note, $indent:expr, [ $( $option:tt )* ] ) => {
+        $crate::snippet_option!(@rev_note $indent, [] [] [ $( $option )* ])
+    };
+
+    ( @rev_note $indent:expr, [] [] [] ) => {
+        ""
+    };
+
+    ( @rev_note $indent:expr, [$rev:tt] [] [] ) => {
+        ""
+    };
+
+    ( @rev_note $indent:expr, [] [$note:tt] [] ) => {
+        $crate::core::compile_error!("`rev_note` requires a `rev` to be specified")
+    };
+
+    ( @rev_note $indent:expr, [$rev:tt] [$note:tt] [] ) => {
+        concat!(
+            $indent,
+            "# pinned to rev ",
+            $rev,
+            " ",
+            $crate::comment_text!("rev_note", $note),
+            "\n",
+        )
+    };
+
+    (
+        @rev_note $indent:expr,
+        $rev:tt $note:tt
+        [ [rev = $new_rev:literal] $( $rest:tt )* ]
+    ) => {
+        $crate::snippet_option!(@rev_note $indent, [$new_rev] $note [ $( $rest )* ])
+    };
+
+    (
+        @rev_note $indent:expr,
+        $rev:tt $note:tt
+        [ [rev_note = $new_note:literal] $( $rest:tt )* ]
+    ) => {
+        $crate::snippet_option!(@rev_note $indent, $rev [$new_note] [ $( $rest )* ])
+    };
+
+    ( @rev_note $indent:expr, $rev:tt $note:tt [ $skip:tt $( $rest:tt )* ] ) => {
+        $crate::snippet_option!(@rev_note $indent, $rev $note [ $( $rest )* ])
+    };
+
+    ( $option:ident, $indent:expr, [] ) => {
+        ""
+    };
+
+    ( $option:ident, $indent:expr, [ $skip:tt $( $rest:tt )* ] ) => {
+        $crate::snippet_option!($option, $indent, [ $( $rest )* ])
+    };
+}
+
+// Looks up the `style` and `precision` options and renders the corresponding
+// version requirement.
+//
+// The version components are read from the environment variables set by cargo
+// when the package description does not provide them.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! snippet_version {
+    ( [$name:expr, $version:expr], $options:tt ) => {
+        $crate::snippet_version!(
+            [
+                $name,
+                $version,
+                [
+                    $crate::core::env!("CARGO_PKG_VERSION_MAJOR"),
+                    $crate::core::env!("CARGO_PKG_VERSION_MINOR"),
+                    $crate::core::env!("CARGO_PKG_VERSION_PATCH"),
+                    $crate::core::env!("CARGO_PKG_VERSION_PRE"),
+                ]
+            ],
+            $options
+        )
+    };
+
+    ( [$name:expr, $version:expr, $components:tt], $options:tt ) => {
+        $crate::snippet_version!(@find [$name, $version, $components] [] [] [] $options)
+    };
+
+    ( @find $package:tt $style:tt $precision:tt [] [] ) => {
+        $crate::snippet_version!(@render $package $style $precision)
+    };
+
+    ( @find $package:tt $style:tt $precision:tt [keep] [] ) => {
+        $crate::snippet_version!(@render $package $style $precision)
+    };
+
+    ( @find $package:tt $style:tt [] [strip] [] ) => {
+        $crate::snippet_version!(@render $package $style [patch])
+    };
+
+    ( @find $package:tt $style:tt $precision:tt [strip] [] ) => {
+        $crate::snippet_version!(@render $package $style $precision)
+    };
+
+    (
+        @find
+        [$name:expr, $version:expr, [$major:expr, $minor:expr, $patch:expr, $pre:expr $(,)?]]
+        $style:tt
+        $precision:tt
+        [$prerelease:ident]
+        []
+    ) => {
+        $crate::proc_macro_backend!(
+            prerelease,
+            [
+                $prerelease,
+                [$pre],
+                $crate::snippet_version!(
+                    @render
+                    [$name, $version, [$major, $minor, $patch, $pre]]
+                    $style
+                    $precision
+                )
+            ]
+        )
+    };
+
+    (
+        @find $package:tt $style:tt $precision:tt $prerelease:tt
+        [ [style = $new:tt] $( $rest:tt )* ]
+    ) => {
+        $crate::snippet_version!(@find $package [$new] $precision $prerelease [ $( $rest )* ])
+    };
+
+    (
+        @find $package:tt $style:tt $precision:tt $prerelease:tt
+        [ [precision = $new:tt] $( $rest:tt )* ]
+    ) => {
+        $crate::snippet_version!(@find $package $style [$new] $prerelease [ $( $rest )* ])
+    };
+
+    (
+        @find $package:tt $style:tt $precision:tt $prerelease:tt
+        [ [prerelease = $new:ident] $( $rest:tt )* ]
+    ) => {
+        $crate::snippet_version!(@find $package $style $precision [$new] [ $( $rest )* ])
+    };
+
+    ( @find $package:tt $style:tt $precision:tt $prerelease:tt [ $skip:tt $( $rest:tt )* ] ) => {
+        $crate::snippet_version!(@find $package $style $precision $prerelease [ $( $rest )* ])
+    };
+
+    ( @render $package:tt [] $precision:tt ) => {
+        $crate::snippet_version!(@truncate $package $precision)
+    };
+
+    ( @render $package:tt [caret] $precision:tt ) => {
+        $crate::snippet_version!(@truncate $package $precision)
+    };
+
+    ( @render $package:tt [exact] $precision:tt ) => {
+        concat!("=", $crate::snippet_version!(@truncate $package $precision))
+    };
+
+    ( @render $package:tt [tilde] [] ) => {
+        concat!("~", $crate::snippet_version!(@truncate $package [minor]))
+    };
+
+    ( @render $package:tt [tilde] $precision:tt ) => {
+        concat!("~", $crate::snippet_version!(@truncate $package $precision))
+    };
+
+    ( @render $package:tt [wildcard] [] ) => {
+        concat!($crate::snippet_version!(@truncate $package [major]), ".*")
+    };
+
+    ( @render $package:tt [wildcard] [patch] ) => {
+        $crate::core::compile_error!("the `wildcard` style can't be used with `precision = patch`")
+    };
+
+    ( @render $package:tt [wildcard] $precision:tt ) => {
+        concat!($crate::snippet_version!(@truncate $package $precision), ".*")
+    };
+
+    ( @render $package:tt [$style:tt] $precision:tt ) => {
+        $crate::core::compile_error!(concat!(
+            "unknown version requirement style `",
+            stringify!($style),
+            "`, expected one of `caret`, `tilde`, `exact` or `wildcard`",
+        ))
+    };
+
+    ( @truncate [$name:expr, $version:expr, $components:tt] [] ) => {
+        $version
+    };
+
+    (
+        @truncate
+        [$name:expr, $version:expr, [$major:expr, $minor:expr, $patch:expr, $pre:expr $(,)?]]
+        [major]
+    ) => {
+        $major
+    };
+
+    (
+        @truncate
+        [$name:expr, $version:expr, [$major:expr, $minor:expr, $patch:expr, $pre:expr $(,)?]]
+        [minor]
+    ) => {
+        concat!($major, ".", $minor)
+    };
+
+    (
+        @truncate
+        [$name:expr, $version:expr, [$major:expr, $minor:expr, $patch:expr, $pre:expr $(,)?]]
+        [patch]
+    ) => {
+        concat!($major, ".", $minor, ".", $patch)
+    };
+
+    ( @truncate $package:tt [$precision:tt] ) => {
+        $crate::core::compile_error!(concat!(
+            "unknown version precision `",
+            stringify!($precision),
+            "`, expected one of `major`, `minor` or `patch`",
+        ))
+    };
+}
+
+// Looks up the `block_indent` option and renders the corresponding amount of
+// spaces.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! snippet_indent {
+    ( [] ) => {
+        ""
+    };
+
+    ( [ [block_indent = $indent:tt] $( $rest:tt )* ] ) => {
+        $crate::spaces!($indent)
+    };
+
+    ( [ $skip:tt $( $rest:tt )* ] ) => {
+        $crate::snippet_indent!([ $( $rest )* ])
+    };
+}
+
+// Renders the `comment` options placed above the dependency, in the order they
+// are passed.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! snippet_comment {
+    ( $indent:expr, $version:expr, [] ) => {
+        ""
+    };
+
+    ( $indent:expr, $version:expr, [ [comment = $text:literal] $( $rest:tt )* ] ) => {
+        concat!(
+            $crate::comment_line!($indent, $text, $version),
+            $crate::snippet_comment!($indent, $version, [ $( $rest )* ]),
+        )
+    };
+
+    ( $indent:expr, $version:expr, [ [comment = $( $value:tt )*] $( $rest:tt )* ] ) => {
+        $crate::core::compile_error!("`comment` expects a string")
+    };
+
+    ( $indent:expr, $version:expr, [ $skip:tt $( $rest:tt )* ] ) => {
+        $crate::snippet_comment!($indent, $version, [ $( $rest )* ])
+    };
+}
+
+// Expands to the text of a snippet option written on a single line, after
+// checking that it contains no line break.
+#[cfg(feature = "proc-macro")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! comment_text {
+    ( $option:literal, $text:literal ) => {
+        $crate::dep_doc_macros::comment_text!([$option, $text])
+    };
+}
+
+// Expands to the text of a snippet option. The text can't be checked without
+// the `proc-macro` feature.
+#[cfg(not(feature = "proc-macro"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! comment_text {
+    ( $option:literal, $text:literal ) => {
+        $text
+    };
+}
+
+// Renders a single comment line, substituting its placeholders.
+#[cfg(feature = "proc-macro")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! comment_line {
+    ( $indent:expr, $text:literal, $version:expr ) => {
+        $crate::dep_doc_macros::comment!([$indent, $text, [$version], [$crate::rust_version!()]])
+    };
+}
+
+// Renders a single comment line. Placeholders can't be substituted without
+// the `proc-macro` feature.
+#[cfg(not(feature = "proc-macro"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! comment_line {
+    ( $indent:expr, $text:literal, $version:expr ) => {
+        concat!($indent, "# ", $text, "\n")
+    };
+}
+
+// Renders the lines of a compatibility matrix, with aligned arrows.
+#[cfg(feature = "proc-macro")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! compat_matrix_lines {
+    ( $indent:expr, [ $( ($left:literal, $right:literal) ),* ] ) => {
+        $crate::dep_doc_macros::compat_matrix!([$indent, [ $( ($left, $right) ),* ]])
+    };
+}
+
+// Renders the lines of a compatibility matrix. The left column can't be padded
+// without the `proc-macro` feature.
+#[cfg(not(feature = "proc-macro"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! compat_matrix_lines {
+    ( $indent:expr, [ $( ($left:literal, $right:literal) ),* ] ) => {
+        concat!($( $indent, "# ", $left, " => ", $right, "\n", )*)
+    };
+}
+
+// Renders the empty line separating two tables of the snippet. With `@html`,
+// the second line break is written as an entity, as an empty line would end
+// the HTML block in Markdown.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! snippet_blank_line {
+    ( [] ) => {
+        "\n\n"
+    };
+
+    ( [ [@html] $( $rest:tt )* ] ) => {
+        "\n&#10;"
+    };
+
+    ( [ $skip:tt $( $rest:tt )* ] ) => {
+        $crate::snippet_blank_line!([ $( $rest )* ])
+    };
+}
+
+// Renders the `raw` lines appended to the code block, in the order they are
+// passed.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! snippet_raw {
+    ( $indent:expr, [] ) => {
+        ""
+    };
+
+    ( $indent:expr, [ [raw = [ $( $line:literal ),* $(,)? ]] $( $rest:tt )* ] ) => {
+        concat!(
+            $( "\n", $indent, $crate::comment_text!("raw", $line), )*
+            $crate::snippet_raw!($indent, [ $( $rest )* ])
+        )
+    };
+
+    ( $indent:expr, [ [raw = $line:literal] $( $rest:tt )* ] ) => {
+        concat!(
+            "\n",
+            $indent,
+            $crate::comment_text!("raw", $line),
+            $crate::snippet_raw!($indent, [ $( $rest )* ]),
+        )
+    };
+
+    ( $indent:expr, [ [raw = $( $value:tt )*] $( $rest:tt )* ] ) => {
+        $crate::core::compile_error!("`raw` expects a string or an array of strings")
+    };
+
+    ( $indent:expr, [ $skip:tt $( $rest:tt )* ] ) => {
+        $crate::snippet_raw!($indent, [ $( $rest )* ])
+    };
+}
+
+// Looks up the language of the code block, which defaults to `TOML`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! snippet_lang {
+    ( [] ) => {
+        "TOML"
+    };
+
+    ( [ [@lang $lang:literal] $( $rest:tt )* ] ) => {
+        $lang
+    };
+
+    ( [ $skip:tt $( $rest:tt )* ] ) => {
+        $crate::snippet_lang!([ $( $rest )* ])
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! spaces {
+    (0) => {
+        ""
+    };
+    (1) => {
+        " "
+    };
+    (2) => {
+        "  "
+    };
+    (3) => {
+        "   "
+    };
+    (4) => {
+        "    "
+    };
+    (5) => {
+        "     "
+    };
+    (6) => {
+        "      "
+    };
+    (7) => {
+        "       "
+    };
+    (8) => {
+        "        "
+    };
+
+    ( $other:tt ) => {
+        $crate::core::compile_error!("`block_indent` must be an integer between 0 and 8")
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! package_import {
+    ([$name:expr, $version:expr $(,)? ], [] $(,)? ) => {
+        concat!(
+            $name,
+            " = \"",
+            $version,
+            "\"",
+        )
+    };
+
+    ([$name:expr, $version:expr $(,)? ], [ $( $rest:tt )* ] $(,)? ) => {
+        $crate::split_options!(
+            render_inline_table
+            [$name, [ [@string "version" = $version] ]]
+            []
+            []
+            $( $rest )*
+        )
+    };
+
+    ( $( $tt:tt )* ) => {
+        $crate::package_import!(
+            [
+                $crate::core::env!("CARGO_PKG_NAME"),
+                $crate::core::env!("CARGO_PKG_VERSION"),
+            ],
+            [ $( $tt )* ]
+        )
+    };
+}
+
+// Renders a dependency entry whose key (`$key`) differs from the name of the
+// crate it refers to (`$package`).
+#[doc(hidden)]
+#[macro_export]
+macro_rules! renamed_import {
+    ([$key:expr, $package:expr, $version:expr $(,)? ], [ $( $rest:tt )* ] $(,)? ) => {
+        $crate::split_options!(
+            render_inline_table
+            [$key, [ [@string "version" = $version] [@string "package" = $package] ]]
+            []
+            []
+            $( $rest )*
+        )
+    };
+}
+
+// Renders a dependency entry inherited from the workspace.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! workspace_import {
+    ( @dotted $name:expr $(,)? ) => {
+        concat!($name, ".workspace = true")
+    };
+
+    ( $name:expr $(,)? ) => {
+        $crate::inline_table!($name, [[workspace = true]])
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! render_inline_table {
+    ( [$key:expr, [ $( $prefix:tt )* ]] [ $( $field:tt )* ] ) => {
+        $crate::inline_table!($key, [ $( $prefix )* $( $field )* ])
+    };
+}
+
+// Renders `$key = { ... }`, where each field is a bracketed list of tokens.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! inline_table {
+    ( $key:expr, [ $first:tt $( $field:tt )* ] ) => {
+        concat!(
+            $crate::check_dependency_keys!($first $( $field )*),
+            $key,
+            " = { ",
+            $crate::render_key! $first,
+            $( ", ", $crate::render_key! $field, )*
+            " }",
+        )
+    };
+}
+
+// Renders `$key = { ... }`, with one array item per line. Inline tables must
+// fit on a single line, but the arrays they contain do not.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! multiline_table {
+    ( $indent:expr, $key:expr, [ $first:tt $( $field:tt )* ] ) => {
+        concat!(
+            $crate::check_dependency_keys!($first $( $field )*),
+            $key,
+            " = { ",
+            $crate::multiline_field!($indent, $first),
+            $( ", ", $crate::multiline_field!($indent, $field), )*
+            " }",
+        )
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! multiline_field {
+    ( $indent:expr, [ $first:ident $( - $rest:ident )* = [ $( $item:literal ),+ $(,)? ] ] ) => {
+        concat!(
+            stringify!($first),
+            $( "-", stringify!($rest), )*
+            " = [",
+            $( "\n", $indent, "    ", stringify!($item), ",", )+
+            "\n",
+            $indent,
+            "]",
+        )
+    };
+
+    ( $indent:expr, $field:tt ) => {
+        $crate::render_key! $field
+    };
+}
+
+// Renders each field on its own line, as in a `[dependencies.name]` table.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! table_fields {
+    ( $indent:expr, [ $first:tt $( $field:tt )* ] ) => {
+        concat!(
+            $crate::check_dependency_keys!($first $( $field )*),
+            $crate::render_key! $first,
+            $( "\n", $indent, $crate::render_key! $field, )*
+        )
+    };
+}
+
+// Expands to an empty string if every field is a key Cargo understands in a
+// dependency declaration, and to a compile error otherwise.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! check_dependency_keys {
+    () => {
+        ""
+    };
+
+    ( [@string $( $field:tt )*] $( $rest:tt )* ) => {
+        $crate::check_dependency_keys!($( $rest )*)
+    };
+
+    ( [version = $( $value:tt )+] $( $rest:tt )* ) => {
+        $crate::check_dependency_keys!($( $rest )*)
+    };
+
+    ( [git = $( $value:tt )+] $( $rest:tt )* ) => {
+        $crate::check_dependency_keys!($( $rest )*)
+    };
+
+    ( [branch = $( $value:tt )+] $( $rest:tt )* ) => {
+        $crate::check_dependency_keys!($( $rest )*)
+    };
+
+    ( [tag = $( $value:tt )+] $( $rest:tt )* ) => {
+        $crate::check_dependency_keys!($( $rest )*)
+    };
+
+    ( [rev = $( $value:tt )+] $( $rest:tt )* ) => {
+        $crate::check_dependency_keys!($( $rest )*)
+    };
+
+    ( [path = $( $value:tt )+] $( $rest:tt )* ) => {
+        $crate::check_dependency_keys!($( $rest )*)
+    };
+
+    ( [registry = $( $value:tt )+] $( $rest:tt )* ) => {
+        $crate::check_dependency_keys!($( $rest )*)
+    };
+
+    ( [registry-index = $( $value:tt )+] $( $rest:tt )* ) => {
+        $crate::check_dependency_keys!($( $rest )*)
+    };
+
+    ( [package = $( $value:tt )+] $( $rest:tt )* ) => {
+        $crate::check_dependency_keys!($( $rest )*)
+    };
+
+    ( [optional = $( $value:tt )+] $( $rest:tt )* ) => {
+        $crate::check_dependency_keys!($( $rest )*)
+    };
+
+    ( [default-features = $( $value:tt )+] $( $rest:tt )* ) => {
+        $crate::check_dependency_keys!($( $rest )*)
+    };
+
+    ( [default_features = $( $value:tt )+] $( $rest:tt )* ) => {
+        $crate::check_dependency_keys!($( $rest )*)
+    };
+
+    ( [features = $( $value:tt )+] $( $rest:tt )* ) => {
+        $crate::check_dependency_keys!($( $rest )*)
+    };
+
+    ( [public = $( $value:tt )+] $( $rest:tt )* ) => {
+        $crate::check_dependency_keys!($( $rest )*)
+    };
+
+    ( [workspace = $( $value:tt )+] $( $rest:tt )* ) => {
+        $crate::check_dependency_keys!($( $rest )*)
+    };
+
+    ( [artifact = $( $value:tt )+] $( $rest:tt )* ) => {
+        $crate::check_dependency_keys!($( $rest )*)
+    };
+
+    ( [lib = $( $value:tt )+] $( $rest:tt )* ) => {
+        $crate::check_dependency_keys!($( $rest )*)
+    };
+
+    ( [target = $( $value:tt )+] $( $rest:tt )* ) => {
+        $crate::check_dependency_keys!($( $rest )*)
+    };
+
+    ( [ $first:ident $( - $more:ident )* = $( $value:tt )+ ] $( $rest:tt )* ) => {
+        $crate::core::compile_error!(concat!(
+            "unknown dependency key `",
+            stringify!($first),
+            $( "-", stringify!($more), )*
+            "`, expected one of `version`, `git`, `branch`, `tag`, `rev`, `path`, ",
+            "`registry`, `registry-index`, `package`, `optional`, `default-features`, ",
+            "`features`, `public`, `workspace`, `artifact`, `lib` or `target`",
+        ))
+    };
+
+    ( [ $flag:ident ] $( $rest:tt )* ) => {
+        $crate::core::compile_error!(concat!(
+            "unknown option `",
+            stringify!($flag),
+            "`, a dependency only accepts `key = value` pairs and the `optional`, ",
+            "`no_default_features` and `no_std` flags",
+        ))
+    };
+
+    ( $field:tt $( $rest:tt )* ) => {
+        $crate::check_dependency_keys!($( $rest )*)
+    };
+}
+
+// Renders a single `key = value` pair.
+//
+// With the `proc-macro` feature, the pair is serialized by `dep_doc_macros`.
+#[cfg(feature = "proc-macro")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! render_key {
+    ( $( $tt:tt )* ) => {
+        $crate::dep_doc_macros::render_key!( $( $tt )* )
+    };
+}
+
+// Renders a single `key = value` pair.
+//
+// `stringify` adds spaces around the dashes of hyphenated keys (such as
+// `default-features`), so the key is rendered one identifier at a time.
+#[cfg(not(feature = "proc-macro"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! render_key {
+    ( @string $key:literal = $value:expr ) => {
+        concat!($key, " = \"", $value, "\"")
+    };
+
+    ( $first:ident $( - $rest:ident )* = $( $value:tt )+ ) => {
+        concat!(
+            stringify!($first),
+            $( "-", stringify!($rest), )*
+            " = ",
+            stringify!( $( $value )+ ),
+        )
+    };
+
+    ( $( $tt:tt )* ) => {
+        stringify!( $( $tt )* )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod dep_doc_inner {
+        use super::*;
+
+        #[test]
+        fn no_additional_tokens() {
+            let left = dep_doc_inner!(["tokio", "1.13.0"], []);
+            let right = "```TOML\n[dependencies]\ntokio = \"1.13.0\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_git_path() {
+            let left = dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [git = "https://github.com/tokio-rs/tokio"]
+            );
+            let right = "```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", git = \"https://github.com/tokio-rs/tokio\" }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_format_marker() {
+            let left = dep_doc_inner!(["tokio", "1.13.0"], [format_marker]);
+            let right = "```TOML\n# dep-doc-format: v1\n[dependencies]\ntokio = \"1.13.0\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_format_marker_and_features() {
+            let left = dep_doc_inner!(["tokio", "1.13.0"], [features = ["macros"], format_marker]);
+            let right = "```TOML\n# dep-doc-format: v1\n[dependencies]\ntokio = { version = \"1.13.0\", features = [\"macros\"] }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_leading_blank_line() {
+            let left = dep_doc_inner!(["tokio", "1.13.0"], [leading_blank_line]);
+            let right = "\n```TOML\n[dependencies]\ntokio = \"1.13.0\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_recommended_features() {
+            let left = dep_doc_inner!(["tokio", "1.13.0"], [recommended_features = ["rt"]]);
+            let right = "```TOML\n[dependencies]\n# recommended minimal set\ntokio = { version = \"1.13.0\", default-features = false, features = [\"rt\"] }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_no_std() {
+            let left = dep_doc_inner!(["tokio", "1.13.0"], [no_std]);
+            let right = "```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", default-features = false }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_no_std_features() {
+            let left = dep_doc_inner!(["tokio", "1.13.0"], [no_std = ["std", "fs"]]);
+            let right = "```TOML\n[dependencies]\n# requires the standard library: `std`, `fs`\ntokio = { version = \"1.13.0\", default-features = false }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_version_last() {
+            let left = dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [
+                    version_position = last,
+                    git = "https://github.com/tokio-rs/tokio",
+                    features = ["rt"]
+                ]
+            );
+            let right = "```TOML\n[dependencies]\ntokio = { git = \"https://github.com/tokio-rs/tokio\", features = [\"rt\"], version = \"1.13.0\" }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_replaced_version_last() {
+            let left = dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [version_position = last, features = ["rt"], version = "1"]
+            );
+            let right =
+                "```TOML\n[dependencies]\ntokio = { features = [\"rt\"], version = \"1\" }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_lone_version_last() {
+            let left = dep_doc_inner!(["tokio", "1.13.0"], [version_position = last]);
+            let right = "```TOML\n[dependencies]\ntokio = \"1.13.0\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_heading() {
+            let left = dep_doc_inner!(["tokio", "1.13.0"], [heading, as = "tk"]);
+            let right = "Add [`tokio`](https://crates.io/crates/tokio) to your `Cargo.toml`:\n\n```TOML\n[dependencies]\ntk = { version = \"1.13.0\", package = \"tokio\" }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_details() {
+            let left = dep_doc_inner!(["tokio", "1.13.0"], [details]);
+            let right = "<details><summary>Cargo.toml</summary>\n\n```TOML\n[dependencies]\ntokio = \"1.13.0\"\n```\n\n</details>";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_details_summary() {
+            let left = dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [block_indent = 2, details = "Manifest", features = ["rt"]]
+            );
+            let right = "  <details><summary>Manifest</summary>\n\n  ```TOML\n  [dependencies]\n  tokio = { version = \"1.13.0\", features = [\"rt\"] }\n  ```\n\n  </details>";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_shorthand_flags() {
+            let left = dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [no_default_features, features = ["rt"], optional]
+            );
+            let right = "```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", default-features = false, features = [\"rt\"], optional = true }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn valid_toml() {
+            crate::valid_snippet(dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [
+                    format_marker,
+                    details,
+                    form = table,
+                    features = ["rt"],
+                    optional_feature = "rt"
+                ]
+            ));
+            crate::valid_snippet(dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [
+                    form = multiline,
+                    features = ["rt", "macros"],
+                    peer("serde", version = "1"),
+                    comment = "Async runtime"
+                ]
+            ));
+        }
+
+        #[test]
+        fn with_hyphenated_key() {
+            let left = dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [default - features = false, features = ["rt", "macros"]]
+            );
+            let right = "```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", default-features = false, features = [\"rt\", \"macros\"] }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_block_indent() {
+            let left = dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [
+                    block_indent = 4,
+                    format_marker,
+                    recommended_features = ["rt"]
+                ]
+            );
+            let right = "    ```TOML\n    # dep-doc-format: v1\n    [dependencies]\n    # recommended minimal set\n    tokio = { version = \"1.13.0\", default-features = false, features = [\"rt\"] }\n    ```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_links() {
+            let left = dep_doc_inner!(["libgit2-sys", "0.12.26"], [links = "git2"]);
+            let right = "```TOML\n[dependencies]\n# links to native library: git2\nlibgit2-sys = \"0.12.26\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_caret_style() {
+            let left = dep_doc_inner!(["tokio", "1.13.0", ["1", "13", "0", ""]], [style = caret]);
+            let right = "```TOML\n[dependencies]\ntokio = \"1.13.0\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_tilde_style() {
+            let left = dep_doc_inner!(
+                ["tokio", "1.13.0", ["1", "13", "0", ""]],
+                [style = tilde, features = ["rt"]]
+            );
+            let right = "```TOML\n[dependencies]\ntokio = { version = \"~1.13\", features = [\"rt\"] }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_exact_style() {
+            let left = dep_doc_inner!(["tokio", "1.13.0", ["1", "13", "0", ""]], [style = exact]);
+            let right = "```TOML\n[dependencies]\ntokio = \"=1.13.0\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_wildcard_style() {
+            let left = dep_doc_inner!(
+                ["tokio", "1.13.0", ["1", "13", "0", ""]],
+                [style = wildcard]
+            );
+            let right = "```TOML\n[dependencies]\ntokio = \"1.*\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_minor_precision() {
+            let left = dep_doc_inner!(
+                ["serde", "1.0.197", ["1", "0", "197", ""]],
+                [precision = minor]
+            );
+            let right = "```TOML\n[dependencies]\nserde = \"1.0\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_major_precision() {
+            let left = dep_doc_inner!(
+                ["serde", "1.0.197", ["1", "0", "197", ""]],
+                [precision = major]
+            );
+            let right = "```TOML\n[dependencies]\nserde = \"1\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_precision_and_prerelease() {
+            let left = dep_doc_inner!(
+                ["tokio", "2.0.0-rc.1+build.5", ["2", "0", "0", "rc.1"]],
+                [precision = patch, style = exact]
+            );
+            let right = "```TOML\n[dependencies]\ntokio = \"=2.0.0\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_tilde_style_and_precision() {
+            let left = dep_doc_inner!(
+                ["tokio", "1.13.0", ["1", "13", "0", ""]],
+                [style = tilde, precision = major]
+            );
+            let right = "```TOML\n[dependencies]\ntokio = \"~1\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_rev_note() {
+            let left = dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [
+                    git = "https://github.com/tokio-rs/tokio",
+                    rev = "abc123",
+                    rev_note = "pending upstream release",
+                ]
+            );
+            let right = "```TOML\n[dependencies]\n# pinned to rev abc123 pending upstream release\ntokio = { version = \"1.13.0\", git = \"https://github.com/tokio-rs/tokio\", rev = \"abc123\" }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_table_form() {
+            let left = dep_doc_inner!(["tokio", "1.13.0"], [form = table, features = ["macros"]]);
+            let right =
+                "```TOML\n[dependencies.tokio]\nversion = \"1.13.0\"\nfeatures = [\"macros\"]\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_table_form_alias_and_companions() {
+            let left = dep_doc_inner!(
+                ["tokio-core", "0.1.0"],
+                [form = table, as = "core", with = ["tokio-macros"]]
+            );
+            let right = "```TOML\n[dependencies.core]\nversion = \"0.1.0\"\npackage = \"tokio-core\"\n\n[dependencies.tokio-macros]\nversion = \"0.1.0\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_multiline_form() {
+            let left = dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [
+                    form = multiline,
+                    git = "https://github.com/tokio-rs/tokio",
+                    features = ["macros", "rt"],
+                    block_indent = 2
+                ]
+            );
+            let right = "  ```TOML\n  [dependencies]\n  tokio = { version = \"1.13.0\", git = \"https://github.com/tokio-rs/tokio\", features = [\n      \"macros\",\n      \"rt\",\n  ] }\n  ```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_registry() {
+            let left = dep_doc_inner!(["tokio", "1.13.0"], [registry = "internal"]);
+            let right = "```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", registry = \"internal\" }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_version_override() {
+            let left = dep_doc_inner!(["tokio", "1.13.0"], [features = ["rt"], version = "1.8"]);
+            let right =
+                "```TOML\n[dependencies]\ntokio = { version = \"1.8\", features = [\"rt\"] }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn without_version() {
+            let left = dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [version = false, git = "https://github.com/tokio-rs/tokio"]
+            );
+            let right = "```TOML\n[dependencies]\ntokio = { git = \"https://github.com/tokio-rs/tokio\" }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_alias() {
+            let left = dep_doc_inner!(["tokio-core", "0.1.0"], [features = ["rt"], as = "core"]);
+            let right = "```TOML\n[dependencies]\ncore = { version = \"0.1.0\", package = \"tokio-core\", features = [\"rt\"] }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_optional_feature() {
+            let left = dep_doc_inner!(["tokio", "1.13.0"], [optional_feature = "async"]);
+            let right = "```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", optional = true }\n\n[features]\n# The `async` feature enables the optional `tokio` dependency.\nasync = [\"dep:tokio\"]\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_alias_and_optional_feature() {
+            let left = dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [as = "tk", optional_feature = "async"]
+            );
+            let right = "```TOML\n[dependencies]\ntk = { version = \"1.13.0\", package = \"tokio\", optional = true }\n\n[features]\n# The `async` feature enables the optional `tk` dependency.\nasync = [\"dep:tk\"]\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_companions() {
+            let left = dep_doc_inner!(
+                ["serde", "1.0.130", ["1", "0", "130", ""]],
+                [
+                    with = ["serde_derive"],
+                    features = ["rc"],
+                    precision = major
+                ]
+            );
+            let right = "```TOML\n[dependencies]\nserde = { version = \"1\", features = [\"rc\"] }\nserde_derive = \"1\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[cfg(feature = "proc-macro")]
+        #[test]
+        fn with_verified_features() {
+            let left = dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [verify_features, features = ["proc-macro"]]
+            );
+            let right = "```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", features = [\"proc-macro\"] }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[cfg(feature = "proc-macro")]
+        #[test]
+        fn with_verified_git() {
+            let left = dep_doc_inner!(
+                ["dep_doc", "0.1.1"],
+                [verify_git, git = "https://github.com/scrabsha/dep-doc.git"]
+            );
+            let right = "```TOML\n[dependencies]\ndep_doc = { version = \"0.1.1\", git = \"https://github.com/scrabsha/dep-doc.git\" }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_git_reference_order() {
+            let left = dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [
+                    tag = "tokio-1.13.0",
+                    features = ["rt"],
+                    git = "https://github.com/tokio-rs/tokio"
+                ]
+            );
+            let right = "```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", features = [\"rt\"], git = \"https://github.com/tokio-rs/tokio\", tag = \"tokio-1.13.0\" }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_default_git() {
+            let left = dep_doc_inner!(
+                ["dep_doc", "0.1.1"],
+                [features = ["proc-macro"], branch = "main"]
+            );
+            let right = concat!(
+                "```TOML\n[dependencies]\ndep_doc = { version = \"0.1.1\", features = [\"proc-macro\"], git = \"",
+                env!("CARGO_PKG_REPOSITORY"),
+                "\", branch = \"main\" }\n```",
+            );
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_peers() {
+            let left = dep_doc_inner!(
+                ["tokio-util", "0.6.9"],
+                [
+                    peer("tokio", version = "1", features = ["rt"]),
+                    features = ["codec"],
+                    peer("bytes", version = "1")
+                ]
+            );
+            let right = "```TOML\n[dependencies]\ntokio-util = { version = \"0.6.9\", features = [\"codec\"] }\ntokio = { version = \"1\", features = [\"rt\"] }\nbytes = \"1\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_peer_flags() {
+            let left = dep_doc_inner!(
+                ["tokio-util", "0.6.9"],
+                [peer("tokio", version = "1", optional, no_default_features)]
+            );
+            let right = "```TOML\n[dependencies]\ntokio-util = \"0.6.9\"\ntokio = { version = \"1\", optional = true, default-features = false }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_peers_table_form() {
+            let left = dep_doc_inner!(
+                ["tokio-util", "0.6.9"],
+                [
+                    form = table,
+                    peer("tokio", version = "1", features = ["rt"])
+                ]
+            );
+            let right = "```TOML\n[dependencies.tokio-util]\nversion = \"0.6.9\"\n\n[dependencies.tokio]\nversion = \"1\"\nfeatures = [\"rt\"]\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_raw_lines() {
+            let left = dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [
+                    raw = "# async runtime",
+                    features = ["rt"],
+                    raw = ["a = 1", "b = 2"]
+                ]
+            );
+            let right = "```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", features = [\"rt\"] }\n# async runtime\na = 1\nb = 2\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_comment() {
+            let left = dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [
+                    comment = "Optional: enable the \"rt\" feature",
+                    features = ["rt"]
+                ]
+            );
+            let right = "```TOML\n[dependencies]\n# Optional: enable the \"rt\" feature\ntokio = { version = \"1.13.0\", features = [\"rt\"] }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[cfg(feature = "proc-macro")]
+        #[test]
+        fn with_comment_placeholders() {
+            let left = dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [
+                    comment = "Requires Rust {msrv} or newer",
+                    comment = "Since {version}"
+                ]
+            );
+            let right = "```TOML\n[dependencies]\n# Requires Rust 1.54 or newer\n# Since 1.13.0\ntokio = \"1.13.0\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_lang() {
+            let left =
+                dep_doc_inner!(["tokio", "1.13.0"], [@lang "toml,no_run", features = ["rt"]]);
+            let right = "```toml,no_run\n[dependencies]\ntokio = { version = \"1.13.0\", features = [\"rt\"] }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_stripped_prerelease() {
+            let left = dep_doc_inner!(
+                ["clap", "2.0.0-rc.1", ["2", "0", "0", "rc.1"]],
+                [prerelease = strip]
+            );
+            let right = "```TOML\n[dependencies]\nclap = \"2.0.0\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[cfg(feature = "proc-macro")]
+        #[test]
+        fn with_pinned_prerelease() {
+            let left = dep_doc_inner!(
+                ["clap", "2.0.0-rc.1", ["2", "0", "0", "rc.1"]],
+                [prerelease = pin]
+            );
+            let right = "```TOML\n[dependencies]\nclap = \"=2.0.0-rc.1\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[cfg(feature = "proc-macro")]
+        #[test]
+        fn with_enabled_features() {
+            // Cargo doesn't tell the crate which features are enabled without a
+            // build script, so only the listed features are rendered.
+            let left = dep_doc_inner!(
+                ["fancy", "0.1.0"],
+                [enabled_features(exclude = ["cli"]), features = ["derive"]]
+            );
+            let right =
+                "```TOML\n[dependencies]\nfancy = { version = \"0.1.0\", features = [\"derive\"] }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[cfg(feature = "proc-macro")]
+        #[test]
+        fn with_relative_path() {
+            let left = dep_doc_inner!(
+                ["dep_doc", "0.1.1"],
+                [version = false, relative_path = "dep_doc_macros"]
+            );
+            let right = "```TOML\n[dependencies]\ndep_doc = { path = \"..\" }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[cfg(feature = "proc-macro")]
+        #[test]
+        fn with_html() {
+            let left = dep_doc_inner!(["tokio", "1.13.0"], [@html, raw = "# <tokio> & co"]);
+            let right = "<div class=\"dep-doc\"><pre>[dependencies]\n\
+                tokio = \"1.13.0\"\n\
+                # &lt;tokio&gt; &amp; co</pre></div>";
+
+            assert_eq!(left, right);
+        }
+
+        #[cfg(feature = "proc-macro")]
+        #[test]
+        fn with_html_blank_lines() {
+            let left = dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [@html, form = table, features = ["rt"], optional_feature = "async"]
+            );
+            let right = "<div class=\"dep-doc\"><pre>[dependencies.tokio]\n\
+                version = \"1.13.0\"\n\
+                features = [\"rt\"]\n\
+                optional = true\n&#10;\
+                [features]\n\
+                # The `async` feature enables the optional `tokio` dependency.\n\
+                async = [\"dep:tokio\"]</pre></div>";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod readme_in_sync {
+        #[test]
+        fn in_sync() {
+            crate::readme_in_sync(
+                "# tokio\r\n\r\n```TOML\r\n[dependencies]\r\ntokio = \"1.13.0\"\r\n```\r\n",
+                "README.md",
+                crate::dep_doc_inner!(["tokio", "1.13.0"], []),
+            );
+        }
+
+        #[test]
+        #[should_panic(expected = "found:\n\n```toml\n[dependencies]\ntokio = \"1.12.0\"\n```")]
+        fn out_of_sync() {
+            crate::readme_in_sync(
+                "```rust\nuse tokio;\n```\n\n```toml\n[dependencies]\ntokio = \"1.12.0\"\n```\n",
+                "README.md",
+                crate::dep_doc_inner!(["tokio", "1.13.0"], []),
+            );
+        }
+
+        #[test]
+        fn own_readme() {
+            assert_readme_in_sync!("README.md");
+        }
+    }
+
+    mod check_tag {
+        #[test]
+        fn matching() {
+            assert_eq!(crate::check_tag("v1.13.0", "1.13.0", "v"), Ok(()));
+            assert_eq!(crate::check_tag("tokio-1.13.0", "1.13.0", "tokio-"), Ok(()));
+        }
+
+        #[test]
+        fn diverging() {
+            let left = crate::check_tag("v1.12.0", "1.13.0", "v");
+            let right = Err("the crate version `1.13.0` does not match the latest git tag `v1.12.0`, expected `v1.13.0`".to_string());
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod spec_inner {
+        #[test]
+        fn no_additional_tokens() {
+            let left = spec_inner!(["tokio", "1.13.0"], []);
+            let right = "tokio = \"1.13.0\"";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_features() {
+            let left = spec_inner!(["tokio", "1.13.0"], [features = ["rt"], form = table]);
+            let right = "tokio = { version = \"1.13.0\", features = [\"rt\"] }";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod upgrade_doc_inner {
+        #[test]
+        fn no_additional_tokens() {
+            let left = upgrade_doc_inner!(["tokio", "1.13.0"], [from = "0.3"]);
+            let right = "```diff\n [dependencies]\n-tokio = \"0.3\"\n+tokio = \"1.13.0\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_features() {
+            let left = upgrade_doc_inner!(["tokio", "1.13.0"], [from = "0.3", features = ["rt"]]);
+            let right = "```diff\n [dependencies]\n-tokio = \"0.3\"\n+tokio = { version = \"1.13.0\", features = [\"rt\"] }\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod script_dep_doc_inner {
+        #[test]
+        fn no_additional_tokens() {
+            let left = script_dep_doc_inner!(["tokio", "1.13.0"], []);
+            let right = "```cargo\n[dependencies]\ntokio = \"1.13.0\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_features() {
+            let left = script_dep_doc_inner!(["tokio", "1.13.0"], [features = ["rt"]]);
+            let right = "```cargo\n[dependencies]\ntokio = { version = \"1.13.0\", features = [\"rt\"] }\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod docsrs_metadata_doc {
+        #[test]
+        fn no_additional_tokens() {
+            let left = docsrs_metadata_doc!();
+            let right = "```TOML\n[package.metadata.docs.rs]\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_fields() {
+            let left = docsrs_metadata_doc!(
+                all - features = true,
+                default - target = "x86_64-unknown-linux-gnu",
+                targets = [],
+            );
+            let right = "```TOML\n[package.metadata.docs.rs]\nall-features = true\ndefault-target = \"x86_64-unknown-linux-gnu\"\ntargets = []\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_lang() {
+            let left = docsrs_metadata_doc!(@lang "toml", all-features = true);
+            let right = "```toml\n[package.metadata.docs.rs]\nall-features = true\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod dep_doc_for_features_inner {
+        #[test]
+        fn single_feature() {
+            let left = dep_doc_for_features_inner!(["tokio", "1.13.0"], ["rt"]);
+            let right = "```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", features = [\"rt\"] }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_additional_tokens() {
+            let left = dep_doc_for_features_inner!(
+                ["tokio", "1.13.0"],
+                ["rt", "macros", default - features = false]
+            );
+            let right = "```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", features = [\"rt\", \"macros\"], default-features = false }\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod with_package {
+        #[test]
+        fn from_cargo() {
+            let left = with_package!(spec_inner[features = ["proc-macro"]]);
+            let right = concat!(
+                "dep_doc = { version = \"",
+                env!("CARGO_PKG_VERSION"),
+                "\", features = [\"proc-macro\"] }",
+            );
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn from_custom_env() {
+            let left = with_package!(
+                dep_doc_inner
+                [name_env = "CARGO_PKG_NAME", version_env = "CARGO_PKG_VERSION_MAJOR", style = exact]
+            );
+            let right = concat!(
+                "```TOML\n[dependencies]\ndep_doc = \"=",
+                env!("CARGO_PKG_VERSION_MAJOR"),
+                "\"\n```",
+            );
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_arguments() {
+            let left = with_package!(
+                target_dep_doc_inner { cfg(windows), }
+                [version_env = "CARGO_PKG_VERSION_MAJOR", features = ["rt"]]
+            );
+            let right = concat!(
+                "```TOML\n[target.'cfg(windows)'.dependencies]\ndep_doc = { version = \"",
+                env!("CARGO_PKG_VERSION_MAJOR"),
+                "\", features = [\"rt\"] }\n```",
+            );
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn cargo_add() {
+            let left = with_package!(
+                cargo_add_doc_inner
+                [version_env = "CARGO_PKG_VERSION_MAJOR", style = exact]
+            );
+            let right = concat!(
+                "```console\n$ cargo add dep_doc@=",
+                env!("CARGO_PKG_VERSION_MAJOR"),
+                "\n```",
+            );
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn renamed_git() {
+            let left = with_package!(
+                renamed_git_dep_doc_inner
+                [git = "https://github.com/scrabsha/dep-doc", package = "dep-doc", version_env = "CARGO_PKG_VERSION_MAJOR"]
+            );
+            let right = concat!(
+                "From crates.io:\n\n```TOML\n[dependencies]\ndep_doc = \"",
+                env!("CARGO_PKG_VERSION_MAJOR"),
+                "\"\n```\n\nFrom the git repository:\n\n```TOML\n[dependencies]\ndep_doc = { version = \"",
+                env!("CARGO_PKG_VERSION_MAJOR"),
+                "\", package = \"dep-doc\", git = \"https://github.com/scrabsha/dep-doc\" }\n```",
+            );
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod dev_dep_doc_inner {
+        use super::*;
+
+        #[test]
+        fn no_additional_tokens() {
+            let left = dev_dep_doc_inner!(["tokio", "1.13.0"], []);
+            let right = "```TOML\n[dev-dependencies]\ntokio = \"1.13.0\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_git_path() {
+            let left = dev_dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [git = "https://github.com/tokio-rs/tokio"]
+            );
+            let right = "```TOML\n[dev-dependencies]\ntokio = { version = \"1.13.0\", git = \"https://github.com/tokio-rs/tokio\" }\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod build_dep_doc_inner {
+        #[test]
+        fn no_additional_tokens() {
+            let left = build_dep_doc_inner!(["cc", "1.0.72"], []);
+            let right = "```TOML\n[build-dependencies]\ncc = \"1.0.72\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_feature() {
+            let left = build_dep_doc_inner!(["cc", "1.0.72"], [features = ["parallel"]]);
+            let right = "```TOML\n[build-dependencies]\ncc = { version = \"1.0.72\", features = [\"parallel\"] }\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod deps_doc_inner {
+        #[test]
+        fn only_self() {
+            let left = deps_doc_inner!(["tokio", "1.13.0"], [self]);
+            let right = "```TOML\n[dependencies]\ntokio = \"1.13.0\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_dependencies() {
+            let left = deps_doc_inner!(
+                ["tokio", "1.13.0"],
+                [
+                    self(features = ["full"]),
+                    "serde" = { version = "1", features = ["derive"] },
+                    "anyhow" = "1",
+                ]
+            );
+            let right = "```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", features = [\"full\"] }\nserde = { version = \"1\", features = [\"derive\"] }\nanyhow = \"1\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn without_self_options() {
+            let left = deps_doc_inner!(["tokio", "1.13.0"], [self, "anyhow" = "1"]);
+            let right = "```TOML\n[dependencies]\ntokio = \"1.13.0\"\nanyhow = \"1\"\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod sections_doc_inner {
+        #[test]
+        fn dependencies_and_dev_dependencies() {
+            let left = sections_doc_inner!(
+                ["tokio", "1.13.0"],
+                [
+                    "dependencies" = [self(features = ["full"]), "bytes" = "1"],
+                    "dev-dependencies" = [
+                        "tokio-test" = self,
+                        "futures" = { version = "0.3", default-features = false },
+                    ],
+                ]
+            );
+            let right = "```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", features = [\"full\"] }\nbytes = \"1\"\n\n[dev-dependencies]\ntokio-test = \"1.13.0\"\nfutures = { version = \"0.3\", default-features = false }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn without_self() {
+            let left = sections_doc_inner!(
+                ["tokio", "1.13.0"],
+                [
+                    "dependencies" = [self],
+                    "dev-dependencies" = ["futures" = "0.3", "anyhow" = "1"],
+                ]
+            );
+            let right = "```TOML\n[dependencies]\ntokio = \"1.13.0\"\n\n[dev-dependencies]\nfutures = \"0.3\"\nanyhow = \"1\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn companion_with_options() {
+            let left = sections_doc_inner!(
+                ["fancy", "0.1.0"],
+                ["dev-dependencies" = ["fancy-test" = self(features = ["mock"])]]
+            );
+            let right = "```TOML\n[dev-dependencies]\nfancy-test = { version = \"0.1.0\", features = [\"mock\"] }\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod target_dep_doc_inner {
+        #[test]
+        fn no_additional_tokens() {
+            let left = target_dep_doc_inner!(["winapi", "0.3.9"], cfg(windows), []);
+            let right = "```TOML\n[target.'cfg(windows)'.dependencies]\nwinapi = \"0.3.9\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_nested_predicate() {
+            let left = target_dep_doc_inner!(
+                ["winapi", "0.3.9"],
+                cfg(all(windows, not(target_arch = "x86"))),
+                [features = ["winuser"]]
+            );
+            let right = "```TOML\n[target.'cfg(all(windows, not(target_arch = \"x86\")))'.dependencies]\nwinapi = { version = \"0.3.9\", features = [\"winuser\"] }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn string_predicate() {
+            let left = target_dep_doc_inner!(
+                ["winapi", "0.3.9"],
+                cfg = "cfg(all(windows, not(target_arch = \"x86\")))",
+                [features = ["winuser"]]
+            );
+            let right = "```TOML\n[target.'cfg(all(windows, not(target_arch = \"x86\")))'.dependencies]\nwinapi = { version = \"0.3.9\", features = [\"winuser\"] }\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod check_cfg_predicate {
+        #[test]
+        fn valid_predicates() {
+            assert_eq!(check_cfg_predicate!(unix), "");
+            assert_eq!(check_cfg_predicate!(target_os = "linux"), "");
+            assert_eq!(
+                check_cfg_predicate!(any(unix, all(windows, not(target_env = "msvc")))),
+                ""
+            );
+        }
+    }
+
+    mod dep_doc_bytes {
+        #[test]
+        fn no_additional_tokens() {
+            const LEFT: &[u8] = dep_doc_bytes!();
+            let right = concat!(
+                "```TOML\n[dependencies]\ndep_doc = \"",
+                env!("CARGO_PKG_VERSION"),
+                "\"\n```"
+            );
+
+            assert_eq!(LEFT, right.as_bytes());
+        }
+
+        #[test]
+        fn with_git_path() {
+            const LEFT: &[u8] = dep_doc_bytes!(git = "https://github.com/scrabsha/dep-doc");
+            let right = concat!(
+                "```TOML\n[dependencies]\ndep_doc = { version = \"",
+                env!("CARGO_PKG_VERSION"),
+                "\", git = \"https://github.com/scrabsha/dep-doc\" }\n```"
+            );
+
+            assert_eq!(LEFT, right.as_bytes());
+        }
+    }
+
+    mod renamed_git_dep_doc_inner {
+        #[test]
+        fn crates_io_and_git() {
+            let left = renamed_git_dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [
+                    git = "https://github.com/tokio-rs/tokio",
+                    package = "tokio-core"
+                ]
+            );
+            let right = "From crates.io:\n\n```TOML\n[dependencies]\ntokio = \"1.13.0\"\n```\n\nFrom the git repository:\n\n```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", package = \"tokio-core\", git = \"https://github.com/tokio-rs/tokio\" }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_branch() {
+            let left = renamed_git_dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [
+                    git = "https://github.com/tokio-rs/tokio",
+                    package = "tokio-core",
+                    branch = "master",
+                ]
+            );
+            let right = "From crates.io:\n\n```TOML\n[dependencies]\ntokio = \"1.13.0\"\n```\n\nFrom the git repository:\n\n```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", package = \"tokio-core\", git = \"https://github.com/tokio-rs/tokio\", branch = \"master\" }\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod multi_target_dep_doc_inner {
+        #[test]
+        fn no_additional_tokens() {
+            let left = multi_target_dep_doc_inner!(["tokio", "1.13.0"], ["unix", "windows"], []);
+            let right = "```TOML\n[target.'cfg(unix)'.dependencies]\ntokio = \"1.13.0\"\n\n[target.'cfg(windows)'.dependencies]\ntokio = \"1.13.0\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_feature() {
+            let left = multi_target_dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                ["unix", "windows"],
+                [features = ["net"]]
+            );
+            let right = "```TOML\n[target.'cfg(unix)'.dependencies]\ntokio = { version = \"1.13.0\", features = [\"net\"] }\n\n[target.'cfg(windows)'.dependencies]\ntokio = { version = \"1.13.0\", features = [\"net\"] }\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod compat_matrix_doc_inner {
+        #[test]
+        fn matrix() {
+            let left = compat_matrix_doc_inner!(
+                ["tokio", "1.13.0"],
+                [("tokio 0.2", "mio 0.6"), ("tokio 1.x", "mio 0.7")],
+                []
+            );
+            let right = "```TOML\n[dependencies]\n# compatibility matrix:\n# tokio 0.2 => mio 0.6\n# tokio 1.x => mio 0.7\ntokio = \"1.13.0\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[cfg(feature = "proc-macro")]
+        #[test]
+        fn uneven_widths() {
+            let left = compat_matrix_doc_inner!(
+                ["tokio", "1.13.0"],
+                [
+                    ("tokio 0.2", "mio 0.6"),
+                    ("tokio 1.13+", "mio 0.8"),
+                    ("é", "none")
+                ],
+                []
+            );
+            let right = "```TOML\n[dependencies]\n# compatibility matrix:\n# tokio 0.2   => mio 0.6\n# tokio 1.13+ => mio 0.8\n# é           => none\ntokio = \"1.13.0\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_feature() {
+            let left = compat_matrix_doc_inner!(
+                ["tokio", "1.13.0"],
+                [("tokio 1.x", "mio 0.7")],
+                [features = ["net"]]
+            );
+            let right = "```TOML\n[dependencies]\n# compatibility matrix:\n# tokio 1.x => mio 0.7\ntokio = { version = \"1.13.0\", features = [\"net\"] }\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod optional_dep_full_doc_inner {
+        #[test]
+        fn feature() {
+            let left = optional_dep_full_doc_inner!(["tokio", "1.13.0"], [feature = "runtime"]);
+            let right = "```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", optional = true }\n\n[features]\n# The `runtime` feature enables the optional `tokio` dependency.\nruntime = [\"dep:tokio\"]\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_feature_list() {
+            let left = optional_dep_full_doc_inner!(
+                ["tokio", "1.13.0"],
+                [feature = "runtime", features = ["rt"]]
+            );
+            let right = "```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", optional = true, features = [\"rt\"] }\n\n[features]\n# The `runtime` feature enables the optional `tokio` dependency.\nruntime = [\"dep:tokio\"]\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod workspace_dep_doc_inner {
+        #[test]
+        fn no_additional_tokens() {
+            let left = workspace_dep_doc_inner!(["tokio", "1.13.0"], []);
+            let right = "```TOML\n[workspace.dependencies]\ntokio = \"1.13.0\"\n```\n\n```TOML\n[dependencies]\ntokio.workspace = true\n```";
 
-    mod dev_dep_doc_inner {
-        use super::*;
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_feature() {
+            let left = workspace_dep_doc_inner!(["tokio", "1.13.0"], [features = ["rt"]]);
+            let right = "```TOML\n[workspace.dependencies]\ntokio = { version = \"1.13.0\", features = [\"rt\"] }\n```\n\n```TOML\n[dependencies]\ntokio.workspace = true\n```";
+
+            assert_eq!(left, right);
+        }
+    }
 
+    mod workspace_pattern_doc_inner {
         #[test]
         fn no_additional_tokens() {
-            let left = dev_dep_doc_inner!(["tokio", "1.13.0"], []);
-            let right = "```TOML\n[dev-dependencies]\ntokio = \"1.13.0\"\n```";
+            let left = workspace_pattern_doc_inner!(["tokio", "1.13.0"], []);
+            let right = "In the workspace `Cargo.toml`:\n\n```TOML\n[workspace.dependencies]\ntokio = \"1.13.0\"\n```\n\nIn the `Cargo.toml` of each member crate:\n\n```TOML\n[dependencies]\ntokio = { workspace = true }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_feature() {
+            let left = workspace_pattern_doc_inner!(["tokio", "1.13.0"], [features = ["rt"]]);
+            let right = "In the workspace `Cargo.toml`:\n\n```TOML\n[workspace.dependencies]\ntokio = { version = \"1.13.0\", features = [\"rt\"] }\n```\n\nIn the `Cargo.toml` of each member crate:\n\n```TOML\n[dependencies]\ntokio = { workspace = true }\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod cargo_add_doc_inner {
+        #[test]
+        fn no_additional_tokens() {
+            let left = cargo_add_doc_inner!(["tokio", "1.13.0"], []);
+            let right = "```console\n$ cargo add tokio@1.13.0\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_features_and_git() {
+            let left = cargo_add_doc_inner!(
+                ["tokio", "1.13.0"],
+                [
+                    features = ["macros", "rt"],
+                    default - features = false,
+                    git = "https://github.com/tokio-rs/tokio",
+                    branch = "master",
+                ]
+            );
+            let right = "```console\n$ cargo add tokio@1.13.0 --features macros,rt --no-default-features --git https://github.com/tokio-rs/tokio --branch master\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_shorthand_flags() {
+            let left = cargo_add_doc_inner!(["tokio", "1.13.0"], [no_default_features, optional]);
+            let right =
+                "```console\n$ cargo add tokio@1.13.0 --no-default-features --optional\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_alias() {
+            let left = cargo_add_doc_inner!(["tokio", "1.13.0"], [as = "tk"]);
+            let right = "```console\n$ cargo add tokio@1.13.0 --rename tk\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_style() {
+            let left =
+                cargo_add_doc_inner!(["tokio", "1.13.0"], [style = exact, features = ["rt"]]);
+            let right = "```console\n$ cargo add tokio@=1.13.0 --features rt\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod installation_doc_inner {
+        #[test]
+        fn no_additional_tokens() {
+            let left = installation_doc_inner!(["tokio", "1.13.0"], []);
+            let right = "```console\n$ cargo add tokio@1.13.0\n```\n\n\
+                Or add the following to your `Cargo.toml`:\n\n\
+                ```TOML\n[dependencies]\ntokio = \"1.13.0\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_features() {
+            let left = installation_doc_inner!(
+                ["tokio", "1.13.0"],
+                [features = ["rt"], default - features = false]
+            );
+            let right = "```console\n$ cargo add tokio@1.13.0 --features rt --no-default-features\n```\n\n\
+                Or add the following to your `Cargo.toml`:\n\n\
+                ```TOML\n[dependencies]\n\
+                tokio = { version = \"1.13.0\", features = [\"rt\"], default-features = false }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_style() {
+            let left =
+                installation_doc_inner!(["tokio", "1.13.0", ["1", "13", "0", ""]], [style = tilde]);
+            let right = "```console\n$ cargo add tokio@~1.13\n```\n\n\
+                Or add the following to your `Cargo.toml`:\n\n\
+                ```TOML\n[dependencies]\ntokio = \"~1.13\"\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod cargo_install_doc_inner {
+        #[test]
+        fn no_additional_tokens() {
+            let left = cargo_install_doc_inner!(["ripgrep", "13.0.0"], []);
+            let right = "```console\n$ cargo install ripgrep --version 13.0.0\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_registry() {
+            let left = cargo_install_doc_inner!(["ripgrep", "13.0.0"], [registry = "internal"]);
+            let right =
+                "```console\n$ cargo install ripgrep --version 13.0.0 --registry internal\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_binstall() {
+            let left = cargo_install_doc_inner!(["ripgrep", "13.0.0"], [binstall]);
+            let right = "```console\n$ cargo install ripgrep --version 13.0.0\n$ cargo binstall ripgrep\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_binstall_and_registry() {
+            let left = cargo_install_doc_inner!(
+                ["ripgrep", "13.0.0"],
+                [registry = "my-registry", binstall]
+            );
+            let right = "```console\n$ cargo install ripgrep --version 13.0.0 --registry my-registry\n$ cargo binstall ripgrep --registry my-registry\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod bazel_doc_inner {
+        #[test]
+        fn no_additional_tokens() {
+            let left = bazel_doc_inner!(["tokio", "1.13.0"], []);
+            let right = "```starlark\n\
+                crate = use_extension(\"@rules_rust//crate_universe:extensions.bzl\", \"crate\")\n\
+                crate.spec(\n    package = \"tokio\",\n    version = \"1.13.0\",\n)\n\
+                crate.from_specs()\nuse_repo(crate, \"crates\")\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_features() {
+            let left = bazel_doc_inner!(
+                ["tokio", "1.13.0"],
+                [default - features = false, features = ["rt", "macros"]]
+            );
+            let right = "```starlark\n\
+                crate = use_extension(\"@rules_rust//crate_universe:extensions.bzl\", \"crate\")\n\
+                crate.spec(\n    package = \"tokio\",\n    version = \"1.13.0\",\n    default_features = False,\n\
+                \x20   features = [\"rt\", \"macros\"],\n)\n\
+                crate.from_specs()\nuse_repo(crate, \"crates\")\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod buck_doc_inner {
+        #[test]
+        fn with_feature() {
+            let left = buck_doc_inner!(["tokio", "1.13.0"], [features = ["rt"]]);
+            let right = "Add the following to `third-party/Cargo.toml`:\n\n\
+                ```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", features = [\"rt\"] }\n```\n\n\
+                Then run `reindeer buckify` and depend on `//third-party:tokio`.";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod feature_dep_doc_inner {
+        #[test]
+        fn feature_and_item() {
+            let left = feature_dep_doc_inner!(
+                ["tokio", "1.13.0"],
+                [feature = "macros", item = "pub use tokio::main;"]
+            );
+            let right = "```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", features = [\"macros\"] }\n```\n\n```rust,ignore\n#[cfg(feature = \"macros\")]\npub use tokio::main;\n```";
 
             assert_eq!(left, right);
         }
 
         #[test]
         fn with_git_path() {
-            let left = dev_dep_doc_inner!(
+            let left = feature_dep_doc_inner!(
                 ["tokio", "1.13.0"],
-                [git = "https://github.com/tokio-rs/tokio"]
+                [
+                    feature = "macros",
+                    item = "pub use tokio::main;",
+                    git = "https://github.com/tokio-rs/tokio",
+                ]
             );
-            let right = "```TOML\n[dev-dependencies]\ntokio = { version = \"1.13.0\", git = \"https://github.com/tokio-rs/tokio\" }\n```";
+            let right = "```TOML\n[dependencies]\ntokio = { version = \"1.13.0\", features = [\"macros\"], git = \"https://github.com/tokio-rs/tokio\" }\n```\n\n```rust,ignore\n#[cfg(feature = \"macros\")]\npub use tokio::main;\n```";
 
             assert_eq!(left, right);
         }
@@ -278,5 +6615,263 @@ mod tests {
 
             assert_eq!(left, right);
         }
+
+        #[test]
+        fn with_hyphenated_key() {
+            let left = package_import!(["tokio", "1.13.0"], [default - features = false]);
+            let right = "tokio = { version = \"1.13.0\", default-features = false }";
+
+            assert_eq!(left, right);
+        }
+    }
+    mod msrv_doc_inner {
+        #[test]
+        fn sentence() {
+            let left = msrv_doc_inner!(["1.54"], []);
+            let right = "The minimum supported Rust version (MSRV) is 1.54.";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn badge() {
+            let left = msrv_doc_inner!(["1.54"], [badge]);
+            let right = "![MSRV](https://img.shields.io/badge/MSRV-1.54-blue)";
+
+            assert_eq!(left, right);
+        }
+    }
+    mod badges_doc_inner {
+        #[test]
+        fn default_badges() {
+            let left = badges_doc_inner!(["tokio", "1.13.0", "MIT"], []);
+            let right = "[![crates.io](https://img.shields.io/crates/v/tokio)](https://crates.io/crates/tokio/1.13.0) \
+                [![docs.rs](https://img.shields.io/docsrs/tokio/1.13.0)](https://docs.rs/tokio/1.13.0) \
+                [![License: MIT](https://img.shields.io/crates/l/tokio/1.13.0)](https://crates.io/crates/tokio/1.13.0)";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_registry() {
+            let left = badges_doc_inner!(
+                ["tokio", "1.13.0", "MIT"],
+                [registry = "internal", msrv = "1.49"]
+            );
+            let right = "![MSRV](https://img.shields.io/badge/MSRV-1.49-blue)";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn with_msrv() {
+            let left = badges_doc_inner!(["tokio", "1.13.0", "MIT"], [msrv = "1.49"]);
+            let right = "[![crates.io](https://img.shields.io/crates/v/tokio)](https://crates.io/crates/tokio/1.13.0) \
+                [![docs.rs](https://img.shields.io/docsrs/tokio/1.13.0)](https://docs.rs/tokio/1.13.0) \
+                [![License: MIT](https://img.shields.io/crates/l/tokio/1.13.0)](https://crates.io/crates/tokio/1.13.0) \
+                ![MSRV](https://img.shields.io/badge/MSRV-1.49-blue)";
+
+            assert_eq!(left, right);
+        }
+    }
+    mod metadata_doc_inner {
+        #[test]
+        fn all_fields() {
+            let left = metadata_doc_inner!(
+                [
+                    "MIT",
+                    "https://github.com/tokio-rs/tokio",
+                    "https://tokio.rs",
+                    "Tokio Contributors"
+                ],
+                []
+            );
+            let right = "- **License:** MIT\n\
+                - **Repository:** <https://github.com/tokio-rs/tokio>\n\
+                - **Homepage:** <https://tokio.rs>\n\
+                - **Authors:** Tokio Contributors";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn selected_fields() {
+            let left = metadata_doc_inner!(
+                [
+                    "MIT",
+                    "https://github.com/tokio-rs/tokio",
+                    "https://tokio.rs",
+                    "Tokio Contributors"
+                ],
+                [repository, license]
+            );
+            let right = "- **Repository:** <https://github.com/tokio-rs/tokio>\n- **License:** MIT";
+
+            assert_eq!(left, right);
+        }
+    }
+    mod docsrs_link_inner {
+        #[test]
+        fn crate_root() {
+            let left = docsrs_link_inner!(["tokio", "1.13.0"], []);
+            let right = "https://docs.rs/tokio/1.13.0";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn item_path() {
+            let left = docsrs_link_inner!(["tokio", "1.13.0"], ["tokio/fn.spawn.html"]);
+            let right = "https://docs.rs/tokio/1.13.0/tokio/fn.spawn.html";
+
+            assert_eq!(left, right);
+        }
+    }
+    mod changelog_link_inner {
+        #[test]
+        fn default_tag_and_file() {
+            let left = changelog_link_inner!(["https://github.com/tokio-rs/tokio", "1.13.0"], []);
+            let right = "https://github.com/tokio-rs/tokio/blob/v1.13.0/CHANGELOG.md";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn custom_tag_and_file() {
+            let left = changelog_link_inner!(
+                ["https://github.com/tokio-rs/tokio", "1.13.0"],
+                [file = "tokio/CHANGELOG.md", tag_prefix = "tokio-"]
+            );
+            let right = "https://github.com/tokio-rs/tokio/blob/tokio-1.13.0/tokio/CHANGELOG.md";
+
+            assert_eq!(left, right);
+        }
+    }
+    mod use_doc_inner {
+        #[test]
+        fn crate_import() {
+            let left = use_doc_inner!(["tokio_util"], []);
+            let right = "```rust\nuse tokio_util;\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn from_package_name() {
+            let left = use_doc!();
+            let right = format!(
+                "```rust\nuse {};\n```",
+                env!("CARGO_PKG_NAME").replace('-', "_")
+            );
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn item_import() {
+            let left = use_doc_inner!(["tokio_util"], ["codec::*"]);
+            let right = "```rust\nuse tokio_util::codec::*;\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    #[cfg(feature = "proc-macro")]
+    mod with_version_inner {
+        #[test]
+        fn placeholders() {
+            let left = with_version_inner!(
+                ["fancy", "1.2.3", "https://github.com/scrabsha/fancy"],
+                "Since version {version}, [`{name}`]({repository}) has a stable builder API."
+            );
+            let right = "Since version 1.2.3, [`fancy`](https://github.com/scrabsha/fancy) has a stable builder API.";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod patch_doc_inner {
+        #[test]
+        fn default_repository() {
+            let left = patch_doc_inner!(
+                ["tokio", "https://github.com/tokio-rs/tokio"],
+                [branch = "master"]
+            );
+            let right = "```TOML\n[patch.crates-io]\ntokio = { git = \"https://github.com/tokio-rs/tokio\", branch = \"master\" }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn custom_repository() {
+            let left = patch_doc_inner!(
+                ["tokio", "https://github.com/tokio-rs/tokio"],
+                [git = "https://github.com/someone/tokio", rev = "abc123"]
+            );
+            let right = "```TOML\n[patch.crates-io]\ntokio = { git = \"https://github.com/someone/tokio\", rev = \"abc123\" }\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn reference_after_repository() {
+            let left = patch_doc_inner!(
+                ["tokio", "https://github.com/tokio-rs/tokio"],
+                [features = ["rt"], tag = "tokio-1.13.0"]
+            );
+            let right = "```TOML\n[patch.crates-io]\ntokio = { git = \"https://github.com/tokio-rs/tokio\", tag = \"tokio-1.13.0\", features = [\"rt\"] }\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+    mod profile_doc_inner {
+        #[test]
+        fn default_keys() {
+            let left = profile_doc_inner!(["image"], []);
+            let right = "```TOML\n[profile.dev.package.image]\nopt-level = 3\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn custom_profile_and_keys() {
+            let left = profile_doc_inner!(
+                ["image"],
+                [
+                    opt - level = 2,
+                    profile = "test",
+                    debug - assertions = false
+                ]
+            );
+            let right = "```TOML\n[profile.test.package.image]\nopt-level = 2\ndebug-assertions = false\n```";
+
+            assert_eq!(left, right);
+        }
+    }
+
+    mod dep_doc_section_inner {
+        #[test]
+        fn custom_header() {
+            let left = dep_doc_section_inner!(
+                ["tokio", "1.13.0"],
+                "target.'cfg(unix)'.dev-dependencies",
+                []
+            );
+            let right = "```TOML\n[target.'cfg(unix)'.dev-dependencies]\ntokio = \"1.13.0\"\n```";
+
+            assert_eq!(left, right);
+        }
+
+        #[test]
+        fn custom_header_and_tokens() {
+            let left = dep_doc_section_inner!(
+                ["tokio", "1.13.0"],
+                "workspace.dependencies",
+                [features = ["macros"]]
+            );
+            let right = "```TOML\n[workspace.dependencies]\ntokio = { version = \"1.13.0\", features = [\"macros\"] }\n```";
+
+            assert_eq!(left, right);
+        }
     }
 }
@@ -0,0 +1,411 @@
+use dep_doc_toml::Value;
+use std::{env, fmt, fs, path::Path};
+
+// The maximum number of features a `Snippet` can enable.
+const MAX_FEATURES: usize = 16;
+
+/// A dependency snippet assembled with method calls instead of the token-based
+/// syntax of [`dep_doc`](crate::dep_doc).
+///
+/// Every method is a `const fn`, so snippets can be declared as constants.
+/// Since the value of a constant can't be used in a `#[doc]` attribute, the
+/// snippet is rendered by a build script with [`Snippet::write`], and included
+/// in the documentation with [`include_snippet`](crate::include_snippet).
+///
+/// At most 16 features can be enabled, enabling more panics.
+///
+/// # Example
+///
+/// In `build.rs`:
+///
+/// ```rust,no_run
+/// use dep_doc::Snippet;
+///
+/// const SNIPPET: Snippet = Snippet::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+///     .section("dev-dependencies")
+///     .feature("derive")
+///     .git("https://github.com/scrabsha/dep-doc");
+///
+/// fn main() {
+///     SNIPPET.write("dev");
+/// }
+/// ```
+///
+/// In `lib.rs`:
+///
+/// ```rust,ignore
+/// #![doc = dep_doc::include_snippet!("dev")]
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Snippet {
+    name: &'static str,
+    version: Option<&'static str>,
+    section: &'static str,
+    features: [&'static str; MAX_FEATURES],
+    feature_count: usize,
+    default_features: bool,
+    optional: bool,
+    git: Option<&'static str>,
+    reference: Option<(&'static str, &'static str)>,
+    path: Option<&'static str>,
+    registry: Option<&'static str>,
+}
+
+impl Snippet {
+    /// Creates a snippet adding version `version` of crate `name` to the
+    /// `[dependencies]` section.
+    pub const fn new(name: &'static str, version: &'static str) -> Snippet {
+        Snippet {
+            name,
+            version: Some(version),
+            section: "dependencies",
+            features: [""; MAX_FEATURES],
+            feature_count: 0,
+            default_features: true,
+            optional: false,
+            git: None,
+            reference: None,
+            path: None,
+            registry: None,
+        }
+    }
+
+    /// Sets the section the dependency is added to, such as
+    /// `"dev-dependencies"` or `"target.'cfg(unix)'.dependencies"`.
+    pub const fn section(mut self, section: &'static str) -> Snippet {
+        self.section = section;
+        self
+    }
+
+    /// Omits the version requirement, for git and path dependencies.
+    pub const fn without_version(mut self) -> Snippet {
+        self.version = None;
+        self
+    }
+
+    /// Enables a feature of the dependency.
+    pub const fn feature(mut self, feature: &'static str) -> Snippet {
+        self.features[self.feature_count] = feature;
+        self.feature_count += 1;
+        self
+    }
+
+    /// Sets whether the default features of the dependency are enabled.
+    pub const fn default_features(mut self, enabled: bool) -> Snippet {
+        self.default_features = enabled;
+        self
+    }
+
+    /// Sets whether the dependency is optional.
+    pub const fn optional(mut self, optional: bool) -> Snippet {
+        self.optional = optional;
+        self
+    }
+
+    /// Sets the git repository the dependency is fetched from.
+    pub const fn git(mut self, url: &'static str) -> Snippet {
+        self.git = Some(url);
+        self
+    }
+
+    /// Sets the branch of the git repository.
+    pub const fn branch(mut self, branch: &'static str) -> Snippet {
+        self.reference = Some(("branch", branch));
+        self
+    }
+
+    /// Sets the tag of the git repository.
+    pub const fn tag(mut self, tag: &'static str) -> Snippet {
+        self.reference = Some(("tag", tag));
+        self
+    }
+
+    /// Sets the commit of the git repository.
+    pub const fn rev(mut self, rev: &'static str) -> Snippet {
+        self.reference = Some(("rev", rev));
+        self
+    }
+
+    /// Sets the local path of the dependency.
+    pub const fn path(mut self, path: &'static str) -> Snippet {
+        self.path = Some(path);
+        self
+    }
+
+    /// Sets the registry the dependency is fetched from.
+    pub const fn registry(mut self, registry: &'static str) -> Snippet {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Writes the snippet to `$OUT_DIR/<name>.md`, where
+    /// [`include_snippet`](crate::include_snippet) finds it.
+    ///
+    /// This is meant to be called from a build script, and panics when
+    /// `OUT_DIR` is not set or when the file can't be written.
+    #[track_caller]
+    pub fn write(&self, name: &str) {
+        let dir = match env::var("OUT_DIR") {
+            Ok(dir) => dir,
+            Err(_) => {
+                panic!("`OUT_DIR` is not set, `Snippet::write` must be called from a build script")
+            }
+        };
+        let path = Path::new(&dir).join(format!("{}.md", name));
+
+        if let Err(e) = fs::write(&path, self.to_string()) {
+            panic!("failed to write `{}`: {}", path.display(), e);
+        }
+    }
+
+    /// Creates the snippet of the package described by the content of a
+    /// `Cargo.toml`, for `cargo dep-doc`.
+    ///
+    /// The options are read from the `[package.metadata.dep-doc]` table, whose
+    /// keys are named after the methods of [`Snippet`]: `section`, `features`,
+    /// `default-features`, `optional`, `git`, `branch`, `tag`, `rev`, `path`
+    /// and `registry`. The version requirement is omitted with
+    /// `version = false`. As the snippet is built at runtime, its strings are
+    /// leaked.
+    #[doc(hidden)]
+    pub fn from_manifest(manifest: &str) -> Result<Snippet, String> {
+        let values = dep_doc_toml::values(manifest)?;
+        let find = |key: &str| {
+            values
+                .iter()
+                .find(|(path, _)| path == key)
+                .map(|(_, value)| value)
+        };
+
+        for field in ["name", "version"].iter() {
+            if find(&format!("package.{}.workspace", field)).is_some() {
+                return Err(format!(
+                    "the `package.{}` field inherited from the workspace is not supported",
+                    field
+                ));
+            }
+        }
+
+        let string = |key: &str| match find(key) {
+            None => Ok(None),
+            Some(Value::String(value)) => Ok(Some(leak(value))),
+            Some(_) => Err(format!(
+                "the `{}` field of `Cargo.toml` must be a string",
+                key
+            )),
+        };
+        let boolean = |key: &str| match find(key) {
+            None => Ok(None),
+            Some(Value::Bool(value)) => Ok(Some(*value)),
+            Some(_) => Err(format!(
+                "the `{}` field of `Cargo.toml` must be a boolean",
+                key
+            )),
+        };
+        let field = |key: &str| {
+            string(key)?.ok_or_else(|| format!("the `{}` field of `Cargo.toml` is not set", key))
+        };
+
+        let mut snippet = Snippet::new(field("package.name")?, field("package.version")?);
+        let metadata = |key: &str| format!("package.metadata.dep-doc.{}", key);
+
+        if let Some(section) = string(&metadata("section"))? {
+            snippet = snippet.section(section);
+        }
+
+        match find(&metadata("features")) {
+            None => {}
+            Some(Value::Array(features)) => {
+                if features.len() > MAX_FEATURES {
+                    return Err(format!(
+                        "a `Snippet` can enable at most {} features",
+                        MAX_FEATURES
+                    ));
+                }
+
+                for feature in features {
+                    match feature {
+                        Value::String(feature) => snippet = snippet.feature(leak(feature)),
+                        _ => {
+                            return Err(format!(
+                                "`{}` must be an array of strings",
+                                metadata("features")
+                            ))
+                        }
+                    }
+                }
+            }
+            Some(_) => {
+                return Err(format!(
+                    "`{}` must be an array of strings",
+                    metadata("features")
+                ))
+            }
+        }
+
+        if boolean(&metadata("version"))? == Some(false) {
+            snippet = snippet.without_version();
+        }
+        if let Some(enabled) = boolean(&metadata("default-features"))? {
+            snippet = snippet.default_features(enabled);
+        }
+        if let Some(optional) = boolean(&metadata("optional"))? {
+            snippet = snippet.optional(optional);
+        }
+        if let Some(git) = string(&metadata("git"))? {
+            snippet = snippet.git(git);
+        }
+        if let Some(branch) = string(&metadata("branch"))? {
+            snippet = snippet.branch(branch);
+        }
+        if let Some(tag) = string(&metadata("tag"))? {
+            snippet = snippet.tag(tag);
+        }
+        if let Some(rev) = string(&metadata("rev"))? {
+            snippet = snippet.rev(rev);
+        }
+        if let Some(path) = string(&metadata("path"))? {
+            snippet = snippet.path(path);
+        }
+        if let Some(registry) = string(&metadata("registry"))? {
+            snippet = snippet.registry(registry);
+        }
+
+        Ok(snippet)
+    }
+
+    fn keys(&self) -> Vec<(&'static str, String)> {
+        let mut keys = Vec::new();
+
+        if let Some(version) = self.version {
+            keys.push(("version", quote(version)));
+        }
+
+        if !self.default_features {
+            keys.push(("default-features", "false".to_string()));
+        }
+
+        if self.feature_count > 0 {
+            let features = self.features[..self.feature_count]
+                .iter()
+                .map(|feature| quote(feature))
+                .collect::<Vec<_>>();
+            keys.push(("features", format!("[{}]", features.join(", "))));
+        }
+
+        if self.optional {
+            keys.push(("optional", "true".to_string()));
+        }
+
+        if let Some(git) = self.git {
+            keys.push(("git", quote(git)));
+        }
+
+        if let Some((key, value)) = self.reference {
+            keys.push((key, quote(value)));
+        }
+
+        if let Some(path) = self.path {
+            keys.push(("path", quote(path)));
+        }
+
+        if let Some(registry) = self.registry {
+            keys.push(("registry", quote(registry)));
+        }
+
+        keys
+    }
+}
+
+impl fmt::Display for Snippet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "```TOML\n[{}]\n{} = ", self.section, self.name)?;
+
+        match self.keys().as_slice() {
+            [] => write!(f, "{{}}")?,
+            [("version", version)] => write!(f, "{}", version)?,
+            keys => {
+                let keys = keys
+                    .iter()
+                    .map(|(key, value)| format!("{} = {}", key, value))
+                    .collect::<Vec<_>>();
+                write!(f, "{{ {} }}", keys.join(", "))?;
+            }
+        }
+
+        write!(f, "\n```")
+    }
+}
+
+// `Snippet` is meant to be built from constants.
+fn leak(value: &str) -> &'static str {
+    Box::leak(value.to_string().into_boxed_str())
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simple() {
+        let left = Snippet::new("tokio", "1.13.0").to_string();
+        let right = "```TOML\n[dependencies]\ntokio = \"1.13.0\"\n```";
+
+        assert_eq!(left, right);
+    }
+
+    #[test]
+    fn with_keys() {
+        const SNIPPET: Snippet = Snippet::new("tokio", "1.13.0")
+            .section("dev-dependencies")
+            .default_features(false)
+            .feature("rt")
+            .feature("macros")
+            .git("https://github.com/tokio-rs/tokio")
+            .tag("tokio-1.13.0");
+
+        let left = SNIPPET.to_string();
+        let right = "```TOML\n[dev-dependencies]\ntokio = { version = \"1.13.0\", default-features = false, features = [\"rt\", \"macros\"], git = \"https://github.com/tokio-rs/tokio\", tag = \"tokio-1.13.0\" }\n```";
+
+        assert_eq!(left, right);
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_many_features() {
+        let mut snippet = Snippet::new("fancy", "0.1.0");
+        for _ in 0..=MAX_FEATURES {
+            snippet = snippet.feature("a");
+        }
+    }
+
+    #[test]
+    fn from_manifest() {
+        let manifest = "[package]\nname = 'fancy'\nversion = \"1.2.3\" # comment\n\n[package.metadata.dep-doc]\nsection = \"dev-dependencies\"\nfeatures = [\"derive\"]\n\n[dependencies]\nname = \"other\"\n";
+
+        let left = Snippet::from_manifest(manifest).unwrap().to_string();
+        let right = "```TOML\n[dev-dependencies]\nfancy = { version = \"1.2.3\", features = [\"derive\"] }\n```";
+
+        assert_eq!(left, right);
+        assert!(
+            Snippet::from_manifest("[package]\nname = \"fancy\"\nversion.workspace = true\n")
+                .is_err()
+        );
+        assert!(Snippet::from_manifest("[package]\nname = \"fancy\"\n").is_err());
+    }
+
+    #[test]
+    fn without_version() {
+        let left = Snippet::new("fancy", "0.1.0")
+            .without_version()
+            .path("crates/fancy")
+            .to_string();
+        let right = "```TOML\n[dependencies]\nfancy = { path = \"crates/fancy\" }\n```";
+
+        assert_eq!(left, right);
+    }
+}
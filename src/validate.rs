@@ -0,0 +1,30 @@
+// Checks that the snippets are valid TOML, with the reader `cargo dep-doc` and
+// the procedural macros read `Cargo.toml` with.
+
+// Checks the content of the first code block of `snippet`.
+pub(crate) fn validate_snippet(snippet: &str) -> Result<(), String> {
+    let mut lines = snippet.lines();
+    if !lines.any(|line| line.trim_start().starts_with("```")) {
+        return Err("the snippet has no code block".to_string());
+    }
+
+    let content = lines
+        .take_while(|line| line.trim() != "```")
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    dep_doc_toml::validate(&content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snippet_code_block() {
+        let snippet = "Some text\n\n  ```TOML\n  [dependencies]\n  tokio = \"1.13.0\"\n  ```";
+
+        assert_eq!(validate_snippet(snippet), Ok(()));
+        assert!(validate_snippet("tokio = \"1.13.0\"").is_err());
+    }
+}